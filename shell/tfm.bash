@@ -0,0 +1,12 @@
+# Source this from ~/.bashrc, or add: eval "$(tui-file-manager shell-init bash)"
+tfm() {
+    local tmp
+    tmp="$(mktemp -t tfm-choosedir.XXXXXX)"
+    tui-file-manager --choosedir "$tmp" "$@"
+    local dir
+    dir="$(cat -- "$tmp")"
+    rm -f -- "$tmp"
+    if [ -n "$dir" ] && [ "$dir" != "$PWD" ]; then
+        cd -- "$dir" || return
+    fi
+}
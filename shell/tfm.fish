@@ -0,0 +1,11 @@
+# Save as ~/.config/fish/functions/tfm.fish, or add to config.fish:
+#   tui-file-manager shell-init fish | source
+function tfm
+    set -l tmp (mktemp -t tfm-choosedir.XXXXXX)
+    tui-file-manager --choosedir $tmp $argv
+    set -l dir (cat -- $tmp)
+    rm -f -- $tmp
+    if test -n "$dir" -a "$dir" != "$PWD"
+        cd -- $dir
+    end
+end
@@ -1,43 +1,162 @@
 use color_eyre::Result;
-use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind};
 use ratatui::{
     DefaultTerminal, Frame,
     buffer::Buffer,
     layout::Rect,
     style::{Style, Stylize},
     text::{Line, Text},
-    widgets::{Block, List, ListDirection, ListState, Paragraph, Widget},
+    widgets::{Block, List, ListState, Paragraph, Widget},
 };
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 use tui_textarea::TextArea;
 
+mod actions;
+mod bookmarks;
+mod bulk_rename;
 mod commands;
-mod config;
+pub mod config;
 mod confirmation;
+pub mod crash;
+mod entry_list;
+pub mod frecency;
+mod goto;
+mod history;
 mod key_handler;
+mod message;
 mod navigation;
+mod notifications;
+mod pane;
+mod pattern_rename;
+mod permissions;
 mod render;
+mod scan;
+mod shell;
+mod storage;
+mod tabs;
+mod view;
+mod watcher;
 
-use crate::file_ops::{self, Directory};
+use crate::file_ops::{self, Directory, Entry, SortKey};
+use actions::Action;
+use bookmarks::{Bookmark, PendingKey};
+use bulk_rename::RenamePlan;
+use config::LayoutMode;
 use confirmation::{ConfirmationDialog, centered_rect};
+use entry_list::EntryList;
+use frecency::Frecency;
+use message::{AppMessage, next_input};
+use notifications::Notification;
+use pattern_rename::PatternRename;
+use permissions::PermissionsDialog;
+use scan::{Rescan, ScanHandle, ScanTarget};
+use shell::{OutputLog, OutputLogPanel};
+use tabs::Tab;
+use watcher::FsWatcher;
 
 #[derive(Debug)]
 pub struct App {
     pub exit: bool,
     pub dir: Directory,
     pub subdir: Option<Directory>,
+    /// Listing of the parent directory, kept only for the miller layout.
+    pub parent_dir: Option<Directory>,
     pub list_state: ListState,
     pub show_confirmation: bool,
     pub show_rename: bool,
     pub show_new_file: bool,
     pub new_file_input: TextArea<'static>,
+    /// Whether the new-file prompt creates a directory.
+    pub new_directory: bool,
     pub file_to_delete: Option<String>,
     pub file_to_rename: Option<String>,
     pub rename_input: TextArea<'static>,
+    /// Why the last rename attempt was refused, shown in the rename dialog.
+    pub rename_error: Option<String>,
     pub clipboard: Option<Clipboard>,
     pub show_hidden_files: bool,
+    pub marked: HashSet<String>,
+    pub show_shell_prompt: bool,
+    pub shell_input: TextArea<'static>,
+    pub show_output_log: bool,
+    pub output_log: OutputLog,
+    pub needs_clear: bool,
+    pub config: config::Cli,
+    pub picked: Vec<String>,
+    pub show_palette: bool,
+    pub palette_input: TextArea<'static>,
+    pub palette_state: ListState,
+    pub watcher: Option<FsWatcher>,
+    /// Sender any subsystem can use to post messages to the main loop.
+    pub messages: UnboundedSender<AppMessage>,
+    message_rx: UnboundedReceiver<AppMessage>,
+    /// Terminal input; detached while a child process owns the terminal.
+    input_events: Option<EventStream>,
+    current_scan: Option<ScanHandle>,
+    /// Listing the running rescan of the current directory fills.
+    rescan: Option<Rescan>,
+    preview_scan: Option<ScanHandle>,
+    parent_scan: Option<ScanHandle>,
+    other_scan: Option<ScanHandle>,
+    next_scan_id: u64,
+    /// Entry to select once the running scan of the current directory finishes.
+    pub pending_selection: Option<String>,
+    /// Scroll offset to restore along with `pending_selection`.
+    pub pending_offset: Option<usize>,
+    /// Last selected entry and scroll offset of each visited directory.
+    pub positions: HashMap<String, (String, usize)>,
+    pub sort_key: SortKey,
+    pub show_filter: bool,
+    pub filter_input: TextArea<'static>,
+    pub layout: LayoutMode,
+    /// Directories this tab has left, oldest first.
+    pub history: Vec<String>,
+    /// Directories we went back from, most recent last.
+    pub forward_history: Vec<String>,
+    pub show_jump_list: bool,
+    pub jump_list_state: ListState,
+    /// All open tabs; the slot at `active_tab` is a placeholder while its
+    /// state is swapped into the fields above.
+    pub tabs: Vec<Tab>,
+    pub active_tab: usize,
+    /// The unfocused pane while the dual-pane mode is on.
+    pub other_pane: Option<Tab>,
+    /// Whether the focused pane is the right-hand one.
+    pub focus_right: bool,
+    /// First key of a two-key command, waiting for the second.
+    pub pending_key: Option<PendingKey>,
+    /// Vim-style marks: a key and the directory it jumps to.
+    pub marks: BTreeMap<char, String>,
+    pub bookmarks: Vec<Bookmark>,
+    pub show_bookmarks: bool,
+    pub bookmarks_state: ListState,
+    pub show_bookmark_input: bool,
+    pub bookmark_input: TextArea<'static>,
+    /// Bookmark being renamed, or `None` when adding one.
+    pub editing_bookmark: Option<usize>,
+    pub frecency: Frecency,
+    pub show_jump_prompt: bool,
+    pub jump_input: TextArea<'static>,
+    pub jump_state: ListState,
+    pub show_goto: bool,
+    pub goto_input: TextArea<'static>,
+    /// Renames waiting for confirmation.
+    pub rename_plan: Option<RenamePlan>,
+    pub pattern_rename: Option<PatternRename>,
+    pub permissions: Option<PermissionsDialog>,
+    /// Everything reported to the user this session, oldest first.
+    pub notifications: Vec<Notification>,
+    /// Number of toasts on screen at the last draw.
+    visible_toasts: usize,
+    pub show_messages: bool,
 }
 
+/// How often timers (e.g. the watcher debounce) are checked.
+const TICK_RATE: Duration = Duration::from_millis(100);
+
 #[derive(Debug)]
 pub struct Clipboard {
     pub cut: bool,
@@ -45,8 +164,34 @@ pub struct Clipboard {
 }
 
 impl App {
-    pub async fn new() -> Self {
-        let current_dir = file_ops::get_current_directory().await.unwrap();
+    pub async fn new(config: config::Cli) -> Self {
+        // A file argument opens its parent directory with the file selected
+        let (current_dir, start_selection) = match &config.path {
+            Some(path) if path.is_file() => {
+                let parent = path
+                    .canonicalize()
+                    .ok()
+                    .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+                    .unwrap_or_default();
+                let file_name = path.file_name().and_then(|n| n.to_str()).map(String::from);
+                (file_ops::get_directory(&parent).await, file_name)
+            }
+            Some(path) => (file_ops::get_directory(path).await, None),
+            None => (file_ops::get_current_directory().await, None),
+        };
+        // fall back to the working directory (or the root) and say why
+        let (current_dir, startup_error) = match current_dir {
+            Ok(dir) => (dir, None),
+            Err(err) => {
+                let fallback = file_ops::get_current_directory()
+                    .await
+                    .unwrap_or_else(|_| Directory::new(String::new(), "/".to_string()));
+                (
+                    fallback,
+                    Some(format!("Can't open the start directory: {err}")),
+                )
+            }
+        };
         let mut list_state = ListState::default();
         list_state.select(Some(0));
 
@@ -56,12 +201,15 @@ impl App {
         let mut new_file_input = TextArea::default();
         new_file_input.set_block(Block::bordered().title("New name"));
 
-        let show_hidden_files = config::load_config().show_hidden_files;
+        let show_hidden_files = config.show_hidden_files;
+        let layout = config.layout;
+        let (messages, message_rx) = mpsc::unbounded_channel();
 
         let mut app = Self {
             exit: false,
             dir: current_dir,
             subdir: None,
+            parent_dir: None,
             list_state,
             show_rename: false,
             show_confirmation: false,
@@ -69,26 +217,114 @@ impl App {
             file_to_delete: None,
             clipboard: None,
             rename_input,
+            rename_error: None,
             new_file_input,
             show_new_file: false,
+            new_directory: false,
             show_hidden_files,
+            marked: HashSet::new(),
+            show_shell_prompt: false,
+            shell_input: TextArea::default(),
+            show_output_log: false,
+            output_log: OutputLog::default(),
+            needs_clear: false,
+            config,
+            picked: Vec::new(),
+            show_palette: false,
+            palette_input: TextArea::default(),
+            palette_state: ListState::default(),
+            watcher: FsWatcher::new(messages.clone()).ok(),
+            messages,
+            message_rx,
+            input_events: Some(EventStream::new()),
+            current_scan: None,
+            rescan: None,
+            preview_scan: None,
+            parent_scan: None,
+            other_scan: None,
+            next_scan_id: 0,
+            pending_selection: start_selection,
+            pending_offset: None,
+            positions: HashMap::new(),
+            sort_key: SortKey::default(),
+            show_filter: false,
+            filter_input: TextArea::default(),
+            layout,
+            history: Vec::new(),
+            forward_history: Vec::new(),
+            show_jump_list: false,
+            jump_list_state: ListState::default(),
+            tabs: vec![Tab::new(Directory::new(String::new(), String::new()))],
+            active_tab: 0,
+            other_pane: None,
+            focus_right: false,
+            pending_key: None,
+            marks: bookmarks::load_marks(),
+            bookmarks: bookmarks::load_bookmarks(),
+            show_bookmarks: false,
+            bookmarks_state: ListState::default(),
+            show_bookmark_input: false,
+            bookmark_input: TextArea::default(),
+            editing_bookmark: None,
+            frecency: Frecency::load(),
+            show_jump_prompt: false,
+            jump_input: TextArea::default(),
+            jump_state: ListState::default(),
+            show_goto: false,
+            goto_input: TextArea::default(),
+            rename_plan: None,
+            pattern_rename: None,
+            permissions: None,
+            notifications: Vec::new(),
+            visible_toasts: 0,
+            show_messages: false,
         };
 
-        app.update_subdir_preview_async().await;
+        if let Some(err) = startup_error {
+            app.notify_error(err);
+        }
+        app.start_scan(ScanTarget::Current);
+        app.update_parent_listing();
         app
     }
 
-    pub async fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
+    pub async fn run(&mut self, mut terminal: DefaultTerminal) -> Result<()> {
+        let mut tick = tokio::time::interval(TICK_RATE);
+        let mut redraw = true;
+
         while !self.exit {
-            terminal.draw(|frame| self.render(frame))?;
-            self.handle_crossterm_events().await?;
+            if self.needs_clear {
+                terminal.clear()?;
+                self.needs_clear = false;
+                redraw = true;
+            }
+            if redraw {
+                terminal.draw(|frame| self.render(frame))?;
+            }
+
+            redraw = tokio::select! {
+                Some(event) = next_input(&mut self.input_events) => {
+                    self.handle_crossterm_events(event?).await?;
+                    true
+                }
+                Some(message) = self.message_rx.recv() => {
+                    self.handle_message(message).await?;
+                    true
+                }
+                _ = tick.tick() => {
+                    self.save_frecency_if_due();
+                    let refreshed = self.refresh_on_fs_changes();
+                    self.toasts_changed() || refreshed
+                }
+            };
+        }
+        if !self.frecency.is_saved() {
+            self.frecency.save()?;
         }
         Ok(())
     }
 
-    async fn handle_crossterm_events(&mut self) -> Result<()> {
-        let event = event::read()?;
-
+    async fn handle_crossterm_events(&mut self, event: Event) -> Result<()> {
         // Handle rename input separately to pass the raw event
         if self.show_rename {
             if let Event::Key(key) = &event
@@ -109,6 +345,110 @@ impl App {
             return Ok(());
         }
 
+        // Handle command palette input separately
+        if self.show_palette {
+            if let Event::Key(key) = &event
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_palette_input(*key).await?;
+            }
+            return Ok(());
+        }
+
+        // Handle the bookmark name prompt and the bookmarks panel separately
+        if self.show_bookmark_input || self.show_bookmarks {
+            if let Event::Key(key) = &event
+                && key.kind == KeyEventKind::Press
+            {
+                if self.show_bookmark_input {
+                    self.handle_bookmark_input(*key);
+                } else {
+                    self.handle_bookmarks_input(*key);
+                }
+            }
+            return Ok(());
+        }
+
+        // Handle the pattern rename dialog separately
+        if self.pattern_rename.is_some() {
+            if let Event::Key(key) = &event
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_pattern_rename_input(*key).await;
+            }
+            return Ok(());
+        }
+
+        // Handle the permissions dialog separately
+        if self.permissions.is_some() {
+            if let Event::Key(key) = &event
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_permissions_input(*key).await;
+            }
+            return Ok(());
+        }
+
+        // Handle the bulk rename preview separately
+        if self.rename_plan.is_some() {
+            if let Event::Key(key) = &event
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_rename_plan_input(*key).await;
+            }
+            return Ok(());
+        }
+
+        // Handle go-to-path input separately
+        if self.show_goto {
+            if let Event::Key(key) = &event
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_goto_input(*key);
+            }
+            return Ok(());
+        }
+
+        // Handle the frecency jump prompt separately
+        if self.show_jump_prompt {
+            if let Event::Key(key) = &event
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_jump_input(*key);
+            }
+            return Ok(());
+        }
+
+        // Handle jump list navigation separately
+        if self.show_jump_list {
+            if let Event::Key(key) = &event
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_jump_list_input(*key);
+            }
+            return Ok(());
+        }
+
+        // Handle filter input separately
+        if self.show_filter {
+            if let Event::Key(key) = &event
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_filter_input(*key).await?;
+            }
+            return Ok(());
+        }
+
+        // Handle shell command input separately
+        if self.show_shell_prompt {
+            if let Event::Key(key) = &event
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_shell_input(*key).await?;
+            }
+            return Ok(());
+        }
+
         match event {
             // it's important to check KeyEventKind::Press to avoid handling key release events
             Event::Key(key) if key.kind == KeyEventKind::Press => self.on_key_event(key).await?,
@@ -122,4 +462,17 @@ impl App {
     fn quit(&mut self) {
         self.exit = true;
     }
+
+    /// Write the `--choosedir` / `--choosefiles` outputs, if requested.
+    pub fn write_choice_files(&self) -> std::io::Result<()> {
+        if let Some(path) = &self.config.choosedir {
+            fs::write(path, &self.dir.path)?;
+        }
+        if let Some(path) = &self.config.choosefiles {
+            let mut chosen = self.marked_or_selected_paths().join("\n");
+            chosen.push('\n');
+            fs::write(path, chosen)?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,486 @@
+use super::*;
+use ratatui::crossterm::event::KeyModifiers;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    SelectNext,
+    SelectPrevious,
+    EnterDirectory,
+    GoToParent,
+    Open,
+    Delete,
+    Rename,
+    BulkRename,
+    PatternRename,
+    Permissions,
+    Yank,
+    NewFile,
+    NewDirectory,
+    Copy,
+    Cut,
+    Paste,
+    ToggleMark,
+    ShellCommand,
+    ToggleOutputLog,
+    Messages,
+    Subshell,
+    CommandPalette,
+    CycleSort,
+    Filter,
+    ToggleLayout,
+    NewTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+    ToggleDualPane,
+    SwitchPane,
+    CopyToOtherPane,
+    MoveToOtherPane,
+    GoBack,
+    GoForward,
+    JumpList,
+    SetMark,
+    JumpToMark,
+    Bookmarks,
+    FrecencyJump,
+    GoToPath,
+}
+
+/// Default key bindings. An action may have several keys; the first one is shown in hints.
+#[rustfmt::skip]
+const KEYMAP: &[(KeyCode, KeyModifiers, Action)] = &[
+    (KeyCode::Char('q'), KeyModifiers::NONE,    Action::Quit),
+    (KeyCode::Esc,       KeyModifiers::NONE,    Action::Quit),
+    (KeyCode::Char('j'), KeyModifiers::NONE,    Action::SelectNext),
+    (KeyCode::Down,      KeyModifiers::NONE,    Action::SelectNext),
+    (KeyCode::Char('k'), KeyModifiers::NONE,    Action::SelectPrevious),
+    (KeyCode::Up,        KeyModifiers::NONE,    Action::SelectPrevious),
+    (KeyCode::Char('l'), KeyModifiers::NONE,    Action::EnterDirectory),
+    (KeyCode::Right,     KeyModifiers::NONE,    Action::EnterDirectory),
+    (KeyCode::Char('h'), KeyModifiers::NONE,    Action::GoToParent),
+    (KeyCode::Left,      KeyModifiers::NONE,    Action::GoToParent),
+    (KeyCode::Enter,     KeyModifiers::NONE,    Action::Open),
+    (KeyCode::Char('d'), KeyModifiers::NONE,    Action::Delete),
+    (KeyCode::Delete,    KeyModifiers::NONE,    Action::Delete),
+    (KeyCode::Backspace, KeyModifiers::NONE,    Action::Delete),
+    (KeyCode::Char('r'), KeyModifiers::NONE,    Action::Rename),
+    (KeyCode::Char('R'), KeyModifiers::NONE,    Action::BulkRename),
+    (KeyCode::Char('P'), KeyModifiers::NONE,    Action::PatternRename),
+    (KeyCode::Char('C'), KeyModifiers::NONE,    Action::Permissions),
+    (KeyCode::Char('y'), KeyModifiers::NONE,    Action::Yank),
+    (KeyCode::Char('a'), KeyModifiers::NONE,    Action::NewFile),
+    (KeyCode::Char('A'), KeyModifiers::NONE,    Action::NewDirectory),
+    (KeyCode::Char('c'), KeyModifiers::NONE,    Action::Copy),
+    (KeyCode::Char('x'), KeyModifiers::NONE,    Action::Cut),
+    (KeyCode::Char('p'), KeyModifiers::NONE,    Action::Paste),
+    (KeyCode::Char(' '), KeyModifiers::NONE,    Action::ToggleMark),
+    (KeyCode::Char('!'), KeyModifiers::NONE,    Action::ShellCommand),
+    (KeyCode::Char('O'), KeyModifiers::NONE,    Action::ToggleOutputLog),
+    (KeyCode::Char('E'), KeyModifiers::NONE,    Action::Messages),
+    (KeyCode::Char('S'), KeyModifiers::NONE,    Action::Subshell),
+    (KeyCode::Char('p'), KeyModifiers::CONTROL, Action::CommandPalette),
+    (KeyCode::Char('s'), KeyModifiers::NONE,    Action::CycleSort),
+    (KeyCode::Char('/'), KeyModifiers::NONE,    Action::Filter),
+    (KeyCode::Char('M'), KeyModifiers::NONE,    Action::ToggleLayout),
+    (KeyCode::Char('t'), KeyModifiers::NONE,    Action::NewTab),
+    (KeyCode::Char('w'), KeyModifiers::NONE,    Action::CloseTab),
+    (KeyCode::Char(']'), KeyModifiers::NONE,    Action::NextTab),
+    (KeyCode::Char('['), KeyModifiers::NONE,    Action::PreviousTab),
+    (KeyCode::Char('D'), KeyModifiers::NONE,    Action::ToggleDualPane),
+    (KeyCode::Tab,       KeyModifiers::NONE,    Action::SwitchPane),
+    (KeyCode::F(5),      KeyModifiers::NONE,    Action::CopyToOtherPane),
+    (KeyCode::F(6),      KeyModifiers::NONE,    Action::MoveToOtherPane),
+    (KeyCode::Char('H'), KeyModifiers::NONE,    Action::GoBack),
+    (KeyCode::Char('L'), KeyModifiers::NONE,    Action::GoForward),
+    (KeyCode::Char('J'), KeyModifiers::NONE,    Action::JumpList),
+    (KeyCode::Char('m'), KeyModifiers::NONE,    Action::SetMark),
+    (KeyCode::Char('\''), KeyModifiers::NONE,   Action::JumpToMark),
+    (KeyCode::Char('B'), KeyModifiers::NONE,    Action::Bookmarks),
+    (KeyCode::Char('z'), KeyModifiers::NONE,    Action::FrecencyJump),
+    (KeyCode::Char('g'), KeyModifiers::NONE,    Action::GoToPath),
+    (KeyCode::Char(':'), KeyModifiers::NONE,    Action::GoToPath),
+];
+
+impl Action {
+    pub const ALL: &[Action] = &[
+        Action::Quit,
+        Action::SelectNext,
+        Action::SelectPrevious,
+        Action::EnterDirectory,
+        Action::GoToParent,
+        Action::Open,
+        Action::Delete,
+        Action::Rename,
+        Action::BulkRename,
+        Action::PatternRename,
+        Action::Permissions,
+        Action::Yank,
+        Action::NewFile,
+        Action::NewDirectory,
+        Action::Copy,
+        Action::Cut,
+        Action::Paste,
+        Action::ToggleMark,
+        Action::ShellCommand,
+        Action::ToggleOutputLog,
+        Action::Messages,
+        Action::Subshell,
+        Action::CommandPalette,
+        Action::CycleSort,
+        Action::Filter,
+        Action::ToggleLayout,
+        Action::NewTab,
+        Action::CloseTab,
+        Action::NextTab,
+        Action::PreviousTab,
+        Action::ToggleDualPane,
+        Action::SwitchPane,
+        Action::CopyToOtherPane,
+        Action::MoveToOtherPane,
+        Action::GoBack,
+        Action::GoForward,
+        Action::JumpList,
+        Action::SetMark,
+        Action::JumpToMark,
+        Action::Bookmarks,
+        Action::FrecencyJump,
+        Action::GoToPath,
+    ];
+
+    pub fn from_key(key: KeyEvent) -> Option<Action> {
+        // Shifted characters arrive with the SHIFT modifier set; the char already encodes it
+        let modifiers = key.modifiers - KeyModifiers::SHIFT;
+        KEYMAP
+            .iter()
+            .find(|(code, mods, _)| *code == key.code && *mods == modifiers)
+            .map(|(_, _, action)| *action)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::SelectNext => "Move down",
+            Action::SelectPrevious => "Move up",
+            Action::EnterDirectory => "Enter directory",
+            Action::GoToParent => "Go to parent",
+            Action::Open => "Open",
+            Action::Delete => "Delete",
+            Action::Rename => "Rename",
+            Action::BulkRename => "Bulk rename",
+            Action::PatternRename => "Pattern rename",
+            Action::Permissions => "Permissions",
+            Action::Yank => "Yank path",
+            Action::NewFile => "New file",
+            Action::NewDirectory => "New directory",
+            Action::Copy => "Copy",
+            Action::Cut => "Cut",
+            Action::Paste => "Paste",
+            Action::ToggleMark => "Toggle mark",
+            Action::ShellCommand => "Run shell command",
+            Action::ToggleOutputLog => "Command output",
+            Action::Messages => "Messages",
+            Action::Subshell => "Open subshell",
+            Action::CommandPalette => "Command palette",
+            Action::CycleSort => "Cycle sort",
+            Action::Filter => "Filter",
+            Action::ToggleLayout => "Toggle miller columns",
+            Action::NewTab => "New tab",
+            Action::CloseTab => "Close tab",
+            Action::NextTab => "Next tab",
+            Action::PreviousTab => "Previous tab",
+            Action::ToggleDualPane => "Toggle dual pane",
+            Action::SwitchPane => "Switch pane",
+            Action::CopyToOtherPane => "Copy to other pane",
+            Action::MoveToOtherPane => "Move to other pane",
+            Action::GoBack => "Back",
+            Action::GoForward => "Forward",
+            Action::JumpList => "Jump list",
+            Action::SetMark => "Set mark",
+            Action::JumpToMark => "Jump to mark",
+            Action::Bookmarks => "Bookmarks",
+            Action::FrecencyJump => "Jump (z)",
+            Action::GoToPath => "Go to path",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "Exit the file manager",
+            Action::SelectNext => "Select the next entry",
+            Action::SelectPrevious => "Select the previous entry",
+            Action::EnterDirectory => "Open the selected directory",
+            Action::GoToParent => "Go up to the parent directory",
+            Action::Open => "Open the selected file with the default application",
+            Action::Delete => "Delete the selected file or directory",
+            Action::Rename => "Rename the selected file or directory",
+            Action::BulkRename => "Edit the names of the marked (or all) entries in $EDITOR",
+            Action::PatternRename => {
+                "Rename the marked (or all) entries with regex, case, counter and date rules"
+            }
+            Action::Permissions => {
+                "Change the mode and owner of the marked (or selected) entries (chmod/chown)"
+            }
+            Action::Yank => "Copy the selected path to the system clipboard",
+            Action::NewFile => {
+                "Create a new file in the current directory (a/b/ makes directories)"
+            }
+            Action::NewDirectory => "Create a directory, including missing parents",
+            Action::Copy => "Copy the selected entry for pasting",
+            Action::Cut => "Cut the selected entry for pasting",
+            Action::Paste => "Paste the copied or cut entry here",
+            Action::ToggleMark => "Mark or unmark the selected entry",
+            Action::ShellCommand => "Run a command with %f, %s and %d placeholders",
+            Action::ToggleOutputLog => "Show output of background commands",
+            Action::Messages => "Show the log of errors and notifications",
+            Action::Subshell => "Launch $SHELL in the current directory",
+            Action::CommandPalette => "Search and run any action",
+            Action::CycleSort => "Sort files by name, size or extension",
+            Action::Filter => "Show only entries whose name contains some text",
+            Action::ToggleLayout => "Switch between two panes and parent/current/preview columns",
+            Action::NewTab => "Open a new tab in the current directory",
+            Action::CloseTab => "Close the current tab",
+            Action::NextTab => "Switch to the tab on the right",
+            Action::PreviousTab => "Switch to the tab on the left",
+            Action::ToggleDualPane => "Show two independent directory panes side by side",
+            Action::SwitchPane => "Move the focus to the other pane",
+            Action::CopyToOtherPane => "Copy the marked or selected entries to the other pane",
+            Action::MoveToOtherPane => "Move the marked or selected entries to the other pane",
+            Action::GoBack => "Go back to the previously visited directory",
+            Action::GoForward => "Go forward again after going back",
+            Action::JumpList => "Pick a directory from the back/forward history",
+            Action::SetMark => "Mark the current directory with the next key pressed",
+            Action::JumpToMark => "Jump to the directory marked with the next key pressed",
+            Action::Bookmarks => "Open, add, rename and reorder named bookmarks",
+            Action::FrecencyJump => "Jump to a frequently or recently visited directory",
+            Action::GoToPath => "Type a path (~ and $VARS allowed) to go to, with Tab completion",
+        }
+    }
+
+    /// Human-readable key binding, e.g. `Ctrl-P`.
+    pub fn keybinding(self) -> String {
+        KEYMAP
+            .iter()
+            .find(|(_, _, action)| *action == self)
+            .map(|(code, mods, _)| {
+                let key = match code {
+                    KeyCode::Char(' ') => "Space".to_string(),
+                    KeyCode::Char(c) if mods.contains(KeyModifiers::CONTROL) => {
+                        c.to_ascii_uppercase().to_string()
+                    }
+                    KeyCode::Char(c) => c.to_string(),
+                    KeyCode::F(n) => format!("F{n}"),
+                    KeyCode::Up => "↑".to_string(),
+                    KeyCode::Down => "↓".to_string(),
+                    KeyCode::Left => "←".to_string(),
+                    KeyCode::Right => "→".to_string(),
+                    other => format!("{other:?}"),
+                };
+                if mods.contains(KeyModifiers::CONTROL) {
+                    format!("Ctrl-{key}")
+                } else {
+                    key
+                }
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Score `text` against a fuzzy `query`: every query char must appear in order.
+/// Consecutive matches and matches at word starts score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last_match: Option<usize> = None;
+
+    for qc in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = text[pos..].iter().position(|&c| c == qc)? + pos;
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        last_match = Some(found);
+        pos = found + 1;
+    }
+    // prefer shorter texts for equal matches
+    Some(score * 100 - text.len() as i64)
+}
+
+impl App {
+    pub async fn perform(&mut self, action: Action) -> Result<()> {
+        crash::record(format!("{} in {}", action.name(), self.dir.path));
+        match action {
+            Action::Quit => self.quit(),
+            Action::SelectNext => {
+                self.select_next();
+                self.update_subdir_preview_async().await;
+            }
+            Action::SelectPrevious => {
+                self.select_previous();
+                self.update_subdir_preview_async().await;
+            }
+            Action::EnterDirectory => self.enter_directory().await?,
+            Action::GoToParent => self.go_to_parent().await?,
+            Action::Open if self.config.pick => self.pick_selection(),
+            Action::Open => self.open_file(),
+            Action::Delete => self.delete_file().await,
+            Action::Rename => self.rename_file(),
+            Action::BulkRename => self.bulk_rename(),
+            Action::PatternRename => self.open_pattern_rename(),
+            Action::Permissions => self.open_permissions(),
+            Action::Yank => self.yank_file(),
+            Action::NewFile => self.new_file(),
+            Action::NewDirectory => self.new_directory(),
+            Action::Copy => self.handle_copy_file(),
+            Action::Cut => self.handle_cut_file(),
+            Action::Paste => self.handle_paste().await,
+            Action::ToggleMark => {
+                self.toggle_mark();
+                self.update_subdir_preview_async().await;
+            }
+            Action::ShellCommand => self.open_shell_prompt(),
+            Action::ToggleOutputLog => self.toggle_output_log(),
+            Action::Messages => self.toggle_messages(),
+            Action::Subshell => self.drop_to_subshell().await,
+            Action::CommandPalette => self.open_palette(),
+            Action::CycleSort => self.cycle_sort(),
+            Action::Filter => self.open_filter_prompt(),
+            Action::ToggleLayout => self.toggle_layout(),
+            Action::NewTab => self.new_tab(),
+            Action::CloseTab => self.close_tab(),
+            Action::NextTab => self.next_tab(),
+            Action::PreviousTab => self.previous_tab(),
+            Action::ToggleDualPane => self.toggle_dual_pane(),
+            Action::SwitchPane => self.switch_pane(),
+            Action::CopyToOtherPane => self.transfer_to_other_pane(false).await,
+            Action::MoveToOtherPane => self.transfer_to_other_pane(true).await,
+            Action::GoBack => self.go_back(),
+            Action::GoForward => self.go_forward(),
+            Action::JumpList => self.open_jump_list(),
+            Action::SetMark => self.pending_key = Some(PendingKey::SetMark),
+            Action::JumpToMark => self.pending_key = Some(PendingKey::JumpToMark),
+            Action::Bookmarks => self.open_bookmarks(),
+            Action::FrecencyJump => self.open_jump_prompt(),
+            Action::GoToPath => self.open_goto_prompt(),
+        }
+        Ok(())
+    }
+
+    pub fn open_palette(&mut self) {
+        self.show_palette = true;
+        self.palette_input = TextArea::default();
+        self.palette_input
+            .set_block(Block::bordered().title("Search actions"));
+        self.palette_state.select(Some(0));
+    }
+
+    /// Actions matching the palette query, best match first.
+    pub fn palette_matches(&self) -> Vec<Action> {
+        let query = self.palette_input.lines().join("");
+        let mut scored: Vec<(i64, Action)> = Action::ALL
+            .iter()
+            .filter(|a| **a != Action::CommandPalette)
+            .filter_map(|a| {
+                let by_name = fuzzy_score(&query, a.name());
+                let by_description = fuzzy_score(&query, a.description()).map(|s| s / 2);
+                by_name.max(by_description).map(|score| (score, *a))
+            })
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, a)| a).collect()
+    }
+
+    pub async fn handle_palette_input(&mut self, key: KeyEvent) -> Result<()> {
+        let matches_len = self.palette_matches().len();
+        match key.code {
+            KeyCode::Enter => {
+                let selected = self
+                    .palette_state
+                    .selected()
+                    .and_then(|i| self.palette_matches().get(i).copied());
+                self.show_palette = false;
+                // post it so the palette is closed on screen before the action runs
+                if let Some(action) = selected {
+                    let _ = self.messages.send(AppMessage::Action(action));
+                }
+            }
+            KeyCode::Esc => {
+                self.show_palette = false;
+            }
+            KeyCode::Down => {
+                if matches_len > 0 {
+                    let i = self
+                        .palette_state
+                        .selected()
+                        .map_or(0, |i| (i + 1) % matches_len);
+                    self.palette_state.select(Some(i));
+                }
+            }
+            KeyCode::Up => {
+                if matches_len > 0 {
+                    let i = self
+                        .palette_state
+                        .selected()
+                        .map_or(0, |i| if i == 0 { matches_len - 1 } else { i - 1 });
+                    self.palette_state.select(Some(i));
+                }
+            }
+            _ => {
+                self.palette_input.input(Event::Key(key));
+                self.palette_state.select(Some(0));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn render_palette(&mut self, frame: &mut Frame) {
+        use ratatui::layout::{Constraint, Layout};
+        use ratatui::style::Color;
+        use ratatui::widgets::{BorderType, Clear, ListItem};
+
+        let area = centered_rect(60, 60, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::bordered()
+            .title(" 🔎 Command Palette ")
+            .title_style(Style::new().bold().cyan())
+            .border_type(BorderType::Rounded)
+            .border_style(Style::new().cyan())
+            .style(Style::new().bg(Color::Rgb(30, 30, 40)));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [input_area, list_area] =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(inner);
+        frame.render_widget(&self.palette_input, input_area);
+
+        let width = list_area.width.saturating_sub(2) as usize;
+        let items: Vec<ListItem> = self
+            .palette_matches()
+            .iter()
+            .map(|action| {
+                let binding = action.keybinding();
+                let label = format!("{} — {}", action.name(), action.description());
+                let padding = width.saturating_sub(label.chars().count() + binding.chars().count());
+                ListItem::new(Line::from(vec![
+                    label.into(),
+                    " ".repeat(padding).into(),
+                    binding.dark_gray(),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::new()
+                    .bg(Color::Rgb(60, 60, 80))
+                    .fg(Color::Rgb(255, 215, 0))
+                    .bold(),
+            )
+            .highlight_symbol("▶ ");
+        frame.render_stateful_widget(list, list_area, &mut self.palette_state);
+    }
+}
@@ -0,0 +1,259 @@
+use super::*;
+use history::directory_at;
+use std::collections::BTreeMap;
+
+const MARKS_FILE: &str = "marks";
+const BOOKMARKS_FILE: &str = "bookmarks";
+
+/// Key that waits for a second key press to complete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendingKey {
+    SetMark,
+    JumpToMark,
+}
+
+#[derive(Debug, Clone)]
+pub struct Bookmark {
+    pub name: String,
+    pub path: String,
+}
+
+pub fn load_marks() -> BTreeMap<char, String> {
+    storage::load_pairs(MARKS_FILE)
+        .into_iter()
+        .filter_map(|(key, path)| Some((key.chars().next()?, path)))
+        .collect()
+}
+
+pub fn load_bookmarks() -> Vec<Bookmark> {
+    storage::load_pairs(BOOKMARKS_FILE)
+        .into_iter()
+        .map(|(name, path)| Bookmark { name, path })
+        .collect()
+}
+
+impl App {
+    /// Finish a two-key command such as `ma` or `'a`.
+    pub fn handle_pending_key(&mut self, pending: PendingKey, key: KeyEvent) {
+        let KeyCode::Char(c) = key.code else {
+            return;
+        };
+        match pending {
+            PendingKey::SetMark => {
+                self.marks.insert(c, self.dir.path.clone());
+                self.save_marks();
+            }
+            PendingKey::JumpToMark => {
+                if let Some(path) = self.marks.get(&c).cloned()
+                    && path != self.dir.path
+                {
+                    self.open_directory(directory_at(&path));
+                }
+            }
+        }
+    }
+
+    fn save_marks(&mut self) {
+        let pairs: Vec<(String, &str)> = self
+            .marks
+            .iter()
+            .map(|(c, path)| (c.to_string(), path.as_str()))
+            .collect();
+        if let Err(err) =
+            storage::save_pairs(MARKS_FILE, pairs.iter().map(|(c, p)| (c.as_str(), *p)))
+        {
+            self.notify_error(format!("Failed to save marks: {err}"));
+        }
+    }
+
+    fn save_bookmarks(&mut self) {
+        let pairs = self
+            .bookmarks
+            .iter()
+            .map(|b| (b.name.as_str(), b.path.as_str()));
+        if let Err(err) = storage::save_pairs(BOOKMARKS_FILE, pairs) {
+            self.notify_error(format!("Failed to save bookmarks: {err}"));
+        }
+    }
+
+    pub fn open_bookmarks(&mut self) {
+        self.show_bookmarks = true;
+        if self.bookmarks_state.selected().is_none() {
+            self.bookmarks_state.select(Some(0));
+        }
+    }
+
+    fn open_bookmark_input(&mut self, editing: Option<usize>) {
+        let name = match editing {
+            Some(i) => self.bookmarks[i].name.clone(),
+            None => self.dir.name.clone(),
+        };
+        self.editing_bookmark = editing;
+        self.show_bookmark_input = true;
+        self.bookmark_input = TextArea::from([name]);
+        self.bookmark_input
+            .move_cursor(tui_textarea::CursorMove::End);
+        self.bookmark_input
+            .set_block(Block::bordered().title("Bookmark name"));
+    }
+
+    pub fn handle_bookmark_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => {
+                self.show_bookmark_input = false;
+                let name = self.bookmark_input.lines().join("").trim().to_string();
+                // tabs would break the file format
+                let name = name.replace('\t', " ");
+                if name.is_empty() {
+                    return;
+                }
+                match self.editing_bookmark {
+                    Some(i) => self.bookmarks[i].name = name,
+                    None => {
+                        self.bookmarks.push(Bookmark {
+                            name,
+                            path: self.dir.path.clone(),
+                        });
+                        self.bookmarks_state.select(Some(self.bookmarks.len() - 1));
+                    }
+                }
+                self.save_bookmarks();
+            }
+            KeyCode::Esc => {
+                self.show_bookmark_input = false;
+            }
+            _ => {
+                self.bookmark_input.input(Event::Key(key));
+            }
+        }
+    }
+
+    pub fn handle_bookmarks_input(&mut self, key: KeyEvent) {
+        let len = self.bookmarks.len();
+        let selected = self.bookmarks_state.selected().filter(|i| *i < len);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.show_bookmarks = false;
+            }
+            KeyCode::Down | KeyCode::Char('j') if len > 0 => {
+                let i = selected.map_or(0, |i| (i + 1) % len);
+                self.bookmarks_state.select(Some(i));
+            }
+            KeyCode::Up | KeyCode::Char('k') if len > 0 => {
+                let i = selected.map_or(0, |i| (i + len - 1) % len);
+                self.bookmarks_state.select(Some(i));
+            }
+            KeyCode::Enter | KeyCode::Char('t') => {
+                if let Some(i) = selected {
+                    self.show_bookmarks = false;
+                    let path = self.bookmarks[i].path.clone();
+                    if key.code == KeyCode::Char('t') {
+                        self.new_tab();
+                    }
+                    if path != self.dir.path {
+                        self.open_directory(directory_at(&path));
+                    }
+                }
+            }
+            KeyCode::Char('a') => self.open_bookmark_input(None),
+            KeyCode::Char('r') if selected.is_some() => self.open_bookmark_input(selected),
+            KeyCode::Char('d') => {
+                if let Some(i) = selected {
+                    self.bookmarks.remove(i);
+                    self.bookmarks_state
+                        .select(Some(i.min(self.bookmarks.len().saturating_sub(1))));
+                    self.save_bookmarks();
+                }
+            }
+            // move the selected bookmark down / up
+            KeyCode::Char('J') => {
+                if let Some(i) = selected
+                    && i + 1 < len
+                {
+                    self.bookmarks.swap(i, i + 1);
+                    self.bookmarks_state.select(Some(i + 1));
+                    self.save_bookmarks();
+                }
+            }
+            KeyCode::Char('K') => {
+                if let Some(i) = selected
+                    && i > 0
+                {
+                    self.bookmarks.swap(i, i - 1);
+                    self.bookmarks_state.select(Some(i - 1));
+                    self.save_bookmarks();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn render_bookmarks(&mut self, frame: &mut Frame) {
+        use ratatui::layout::{Constraint, Layout};
+        use ratatui::style::Color;
+        use ratatui::widgets::{BorderType, Clear, ListItem};
+
+        let area = centered_rect(60, 60, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::bordered()
+            .title(" 🔖 Bookmarks ")
+            .title_style(Style::new().bold().cyan())
+            .border_type(BorderType::Rounded)
+            .border_style(Style::new().cyan())
+            .style(Style::new().bg(Color::Rgb(30, 30, 40)));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [list_area, hint_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+
+        let name_width = self
+            .bookmarks
+            .iter()
+            .map(|b| b.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let items: Vec<ListItem> = self
+            .bookmarks
+            .iter()
+            .map(|b| {
+                ListItem::new(Line::from(vec![
+                    format!("{:name_width$}  ", b.name).into(),
+                    b.path.clone().dark_gray(),
+                ]))
+            })
+            .collect();
+        let list = if items.is_empty() {
+            List::new(["  No bookmarks yet — press a to add the current directory"])
+        } else {
+            List::new(items)
+        };
+        let list = list
+            .highlight_style(
+                Style::new()
+                    .bg(Color::Rgb(60, 60, 80))
+                    .fg(Color::Rgb(255, 215, 0))
+                    .bold(),
+            )
+            .highlight_symbol("▶ ");
+        if self.bookmarks.is_empty() {
+            frame.render_widget(list, list_area);
+        } else {
+            frame.render_stateful_widget(list, list_area, &mut self.bookmarks_state);
+        }
+
+        frame.render_widget(
+            Paragraph::new(
+                " Enter:Open │ t:New tab │ a:Add │ r:Rename │ d:Delete │ J/K:Move │ Esc:Close",
+            )
+            .dark_gray(),
+            hint_area,
+        );
+
+        if self.show_bookmark_input {
+            let area = centered_rect(50, 20, frame.area());
+            frame.render_widget(Clear, area);
+            frame.render_widget(&self.bookmark_input, area);
+        }
+    }
+}
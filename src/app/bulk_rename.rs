@@ -0,0 +1,377 @@
+use super::*;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Create a file for the editor that only we can read or write, under a name
+/// others can't guess, so it can't be planted or swapped in a shared temp dir.
+fn create_private_temp_file() -> io::Result<(PathBuf, fs::File)> {
+    use std::hash::{BuildHasher, RandomState};
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    for attempt in 0..16 {
+        let suffix = RandomState::new().hash_one((std::process::id(), attempt));
+        let path = std::env::temp_dir().join(format!("tfm-bulk-rename-{suffix:016x}.txt"));
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    Err(io::ErrorKind::AlreadyExists.into())
+}
+
+/// A set of renames within one directory, checked before anything is touched.
+#[derive(Debug, Default)]
+pub struct RenamePlan {
+    /// `(old, new)` names of the entries that change.
+    pub renames: Vec<(String, String)>,
+    /// Problems that prevent the plan from being applied.
+    pub errors: Vec<String>,
+    /// Some entry is renamed to a name another one is leaving (a→b, b→a),
+    /// so the renames go through temporary names.
+    pub has_cycle: bool,
+}
+
+impl RenamePlan {
+    /// Check renaming `old[i]` to `new[i]` among `existing` names of a directory.
+    pub fn new(old: &[String], new: &[String], existing: &HashSet<String>) -> Self {
+        let mut plan = RenamePlan::default();
+        if old.len() != new.len() {
+            plan.errors.push(format!(
+                "expected {} names but got {} lines",
+                old.len(),
+                new.len()
+            ));
+            return plan;
+        }
+
+        let sources: HashSet<&str> = old.iter().map(String::as_str).collect();
+        let mut targets: HashMap<&str, &str> = HashMap::new();
+        for (old, new) in old.iter().zip(new) {
+            if let Err(err) = commands::validate_name(new) {
+                plan.errors.push(format!("{old}: {err}"));
+            } else if let Some(other) = targets.insert(new, old) {
+                plan.errors
+                    .push(format!("{other} and {old} would both be named {new}"));
+            }
+            if old == new {
+                continue;
+            }
+            if existing.contains(new) && !sources.contains(new.as_str()) {
+                plan.errors.push(format!("{new} already exists"));
+            }
+            plan.renames.push((old.clone(), new.clone()));
+        }
+
+        // a target that is itself being renamed away needs a two-step rename
+        let leaving: HashSet<&str> = plan.renames.iter().map(|(o, _)| o.as_str()).collect();
+        plan.has_cycle = plan
+            .renames
+            .iter()
+            .any(|(_, new)| leaving.contains(new.as_str()));
+        plan
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Perform the renames in `dir`. Returns the errors of any that failed.
+    pub fn apply(&self, dir: &str) -> Vec<String> {
+        let dir = Path::new(dir);
+        let mut failures = Vec::new();
+        if !self.has_cycle {
+            for (old, new) in &self.renames {
+                if let Err(err) = fs::rename(dir.join(old), dir.join(new)) {
+                    failures.push(format!("{old} → {new}: {err}"));
+                }
+            }
+            return failures;
+        }
+
+        // move everything out of the way first, then to the final names
+        let mut staged = Vec::new();
+        for (i, (old, new)) in self.renames.iter().enumerate() {
+            let temp = format!(".tfm-rename-{}-{i}", std::process::id());
+            match fs::rename(dir.join(old), dir.join(&temp)) {
+                Ok(()) => staged.push((temp, old, new)),
+                Err(err) => failures.push(format!("{old} → {new}: {err}")),
+            }
+        }
+        for (temp, old, new) in staged {
+            if let Err(err) = fs::rename(dir.join(&temp), dir.join(new)) {
+                failures.push(format!("{old} → {new}: {err} (left as {temp})"));
+            }
+        }
+        failures
+    }
+}
+
+/// The names in the edited file, one per line, without trailing empty lines.
+/// Lines are taken as they are apart from a `\r` line ending, since names
+/// may start or end with spaces.
+fn edited_names(edited: &str) -> Vec<String> {
+    let mut names: Vec<String> = edited
+        .lines()
+        .map(|line| line.strip_suffix('\r').unwrap_or(line).to_string())
+        .collect();
+    while names.last().is_some_and(String::is_empty) {
+        names.pop();
+    }
+    names
+}
+
+impl App {
+    /// Names of the marked entries, or of every listed entry when none are marked.
+    pub fn bulk_rename_names(&self) -> Vec<String> {
+        self.dir
+            .iter()
+            .filter(|e| self.marked.is_empty() || self.marked.contains(&e.label()))
+            .map(|e| e.name().to_string())
+            .collect()
+    }
+
+    /// Edit the names of the marked (or all) entries in `$EDITOR`, then show
+    /// the resulting renames for confirmation.
+    pub fn bulk_rename(&mut self) {
+        let old = self.bulk_rename_names();
+        if old.is_empty() {
+            return;
+        }
+        let file = match create_private_temp_file() {
+            Ok((path, mut file)) => match file.write_all((old.join("\n") + "\n").as_bytes()) {
+                Ok(()) => path,
+                Err(err) => {
+                    let _ = fs::remove_file(&path);
+                    self.notify_error(format!("Failed to write {}: {err}", path.display()));
+                    return;
+                }
+            },
+            Err(err) => {
+                self.notify_error(format!("Failed to create a temporary file: {err}"));
+                return;
+            }
+        };
+
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".to_string());
+        self.suspend_tui();
+        // run through the shell so EDITOR may contain arguments
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(format!("{editor} \"$1\""))
+            .arg("sh")
+            .arg(&file)
+            .current_dir(&self.dir.path)
+            .status();
+        self.resume_tui();
+
+        let edited = fs::read_to_string(&file);
+        let _ = fs::remove_file(&file);
+        let edited = match (status, edited) {
+            (Ok(status), _) if !status.success() => {
+                self.notify_warn(format!("Editor exited with {status}, nothing renamed"));
+                return;
+            }
+            (Err(err), _) | (_, Err(err)) => {
+                self.notify_error(format!("Bulk rename failed: {err}"));
+                return;
+            }
+            (Ok(_), Ok(edited)) => edited,
+        };
+        let new = edited_names(&edited);
+        self.rename_plan = Some(RenamePlan::new(&old, &new, &self.existing_names()));
+    }
+
+    /// Names on disk in the current directory, including hidden ones.
+    pub fn existing_names(&self) -> HashSet<String> {
+        fs::read_dir(&self.dir.path)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|e| e.file_name().into_string().ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub async fn handle_rename_plan_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('y') => {
+                if let Some(plan) = self.rename_plan.take()
+                    && plan.is_valid()
+                {
+                    self.apply_rename_plan(plan).await;
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('q') => {
+                self.rename_plan = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Rename the entries and rescan, selecting the first renamed one.
+    pub async fn apply_rename_plan(&mut self, plan: RenamePlan) {
+        let failures = plan.apply(&self.dir.path);
+        let renamed = plan.renames.len() - failures.len();
+        for failure in failures {
+            self.notify_error(format!("Failed to rename {failure}"));
+        }
+        if renamed > 0 {
+            self.notify_info(format!("Renamed {renamed} entries"));
+        }
+        self.marked.clear();
+        self.rescan();
+        if let Some((_, new)) = plan.renames.first() {
+            self.pending_selection = Some(new.clone());
+        }
+    }
+
+    pub fn render_rename_plan(&self, frame: &mut Frame) {
+        use ratatui::style::Color;
+        use ratatui::widgets::{BorderType, Clear};
+
+        let Some(plan) = &self.rename_plan else {
+            return;
+        };
+        let area = centered_rect(70, 70, frame.area());
+        frame.render_widget(Clear, area);
+
+        let mut lines: Vec<Line> = plan
+            .errors
+            .iter()
+            .map(|err| Line::from(format!("✗ {err}")).red())
+            .collect();
+        if !lines.is_empty() {
+            lines.push(Line::default());
+        }
+        if plan.renames.is_empty() {
+            lines.push(Line::from("Nothing to rename").dark_gray());
+        }
+        for (old, new) in &plan.renames {
+            lines.push(Line::from(vec![
+                format!("- {old}").red(),
+                "  →  ".dark_gray(),
+                format!("+ {new}").green(),
+            ]));
+        }
+        if plan.has_cycle {
+            lines.push(Line::default());
+            lines.push(Line::from("Names are swapped; renaming through temporary names").yellow());
+        }
+
+        let hint = if plan.is_valid() && !plan.renames.is_empty() {
+            " Enter/y: Apply │ Esc/n: Cancel "
+        } else {
+            " Esc: Close "
+        };
+        let block = Block::bordered()
+            .title(" ✏️  Bulk Rename ")
+            .title_bottom(hint)
+            .title_style(Style::new().bold().yellow())
+            .border_type(BorderType::Rounded)
+            .border_style(Style::new().yellow())
+            .style(Style::new().bg(Color::Rgb(30, 30, 40)));
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn plan(old: &[&str], new: &[&str], existing: &[&str]) -> RenamePlan {
+        let existing = existing.iter().map(|n| n.to_string()).collect();
+        RenamePlan::new(&names(old), &names(new), &existing)
+    }
+
+    #[test]
+    fn swap_goes_through_temporary_names() {
+        let plan = plan(&["a", "b"], &["b", "a"], &["a", "b"]);
+        assert!(plan.is_valid(), "{:?}", plan.errors);
+        assert!(plan.has_cycle);
+        assert_eq!(plan.renames.len(), 2);
+    }
+
+    #[test]
+    fn chain_onto_a_name_being_freed_is_allowed() {
+        let plan = plan(&["a", "b"], &["b", "c"], &["a", "b"]);
+        assert!(plan.is_valid(), "{:?}", plan.errors);
+        assert!(plan.has_cycle);
+        assert_eq!(
+            plan.renames,
+            vec![
+                ("a".to_string(), "b".to_string()),
+                ("b".to_string(), "c".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn unchanged_names_are_skipped() {
+        let plan = plan(&["a", "b"], &["a", "c"], &["a", "b"]);
+        assert!(plan.is_valid());
+        assert!(!plan.has_cycle);
+        assert_eq!(plan.renames, vec![("b".to_string(), "c".to_string())]);
+    }
+
+    #[test]
+    fn duplicate_targets_are_rejected() {
+        let plan = plan(&["a", "b"], &["x", "x"], &["a", "b"]);
+        assert!(!plan.is_valid());
+        assert!(plan.errors[0].contains("would both be named x"));
+    }
+
+    #[test]
+    fn existing_names_are_not_overwritten() {
+        let plan = plan(&["a"], &["c"], &["a", "c"]);
+        assert_eq!(plan.errors, vec!["c already exists".to_string()]);
+    }
+
+    #[test]
+    fn line_count_must_match() {
+        let plan = plan(&["a", "b"], &["a"], &["a", "b"]);
+        assert!(!plan.is_valid());
+        assert!(plan.renames.is_empty());
+        assert!(plan.errors[0].contains("expected 2 names but got 1 lines"));
+    }
+
+    #[test]
+    fn untouched_names_with_spaces_are_kept() {
+        let edited = edited_names(" padded \nb\r\n\n");
+        assert_eq!(edited, names(&[" padded ", "b"]));
+        let old = names(&[" padded ", "b"]);
+        let plan = RenamePlan::new(&old, &edited, &old.iter().cloned().collect());
+        assert!(plan.is_valid(), "{:?}", plan.errors);
+        assert!(plan.renames.is_empty());
+    }
+
+    #[test]
+    fn apply_swaps_files() {
+        let dir = crate::test_util::temp_dir("swap");
+        fs::write(dir.join("a"), "a").unwrap();
+        fs::write(dir.join("b"), "b").unwrap();
+
+        let plan = plan(&["a", "b"], &["b", "a"], &["a", "b"]);
+        let failures = plan.apply(dir.to_str().unwrap());
+        let (a, b) = (
+            fs::read_to_string(dir.join("a")).unwrap(),
+            fs::read_to_string(dir.join("b")).unwrap(),
+        );
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(failures.is_empty(), "{failures:?}");
+        assert_eq!((a.as_str(), b.as_str()), ("b", "a"));
+    }
+}
@@ -1,82 +1,188 @@
 use super::*;
+use std::path::{Component, Path};
+
+/// Longest file name most filesystems accept, in bytes.
+const MAX_NAME_LEN: usize = 255;
+
+/// Check that `name` can be used as a single file name.
+pub fn validate_name(name: &str) -> std::result::Result<(), String> {
+    if name.is_empty() {
+        return Err("Name can't be empty".to_string());
+    }
+    if name == "." || name == ".." {
+        return Err(format!("'{name}' is not a valid name"));
+    }
+    if let Some(c) = name.chars().find(|c| *c == '/' || *c == '\0') {
+        return Err(format!("Names can't contain {c:?}"));
+    }
+    if name.len() > MAX_NAME_LEN {
+        return Err(format!("Name is too long (max {MAX_NAME_LEN} bytes)"));
+    }
+    Ok(())
+}
+
+/// Whether two entries are the same file, e.g. two spellings of one name on a
+/// case-insensitive filesystem.
+#[cfg(unix)]
+fn same_file(a: &fs::Metadata, b: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    (a.dev(), a.ino()) == (b.dev(), b.ino())
+}
+
+#[cfg(not(unix))]
+fn same_file(_a: &fs::Metadata, _b: &fs::Metadata) -> bool {
+    false
+}
+
+/// Create the file or directory `name` (a relative path, possibly nested)
+/// in `dir`, with any missing parent directories. A trailing `/` makes it a
+/// directory.
+fn create_in(dir: &Path, name: &str, directory: bool) -> std::io::Result<()> {
+    // stay inside `dir`
+    let inside = Path::new(name)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if !inside {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "absolute paths and '..' are not allowed",
+        ));
+    }
+    let path = dir.join(name);
+    if directory || name.ends_with('/') {
+        return fs::create_dir_all(&path);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .map(|_| ())
+}
+
+/// The entry of the current directory that creating `name` adds: its top-level
+/// directory for nested paths.
+fn created_entry(name: &str) -> Option<String> {
+    Path::new(name).components().find_map(|c| match c {
+        Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+        _ => None,
+    })
+}
+
+/// Rename `old` to `new` in `dir` without replacing an existing entry.
+fn rename_in(dir: &Path, old: &str, new: &str) -> std::result::Result<(), String> {
+    validate_name(new)?;
+    let (old_path, new_path) = (dir.join(old), dir.join(new));
+    if let Ok(existing) = fs::symlink_metadata(&new_path) {
+        // a case-only change on a case-insensitive filesystem finds the file
+        // itself; a hard link to it is another name that rename would keep
+        let is_itself = new.to_lowercase() == old.to_lowercase()
+            && fs::symlink_metadata(&old_path).is_ok_and(|m| same_file(&m, &existing));
+        if !is_itself {
+            return Err(format!("'{new}' already exists"));
+        }
+    }
+    fs::rename(old_path, new_path).map_err(|err| format!("Rename failed: {err}"))
+}
 
 impl App {
+    /// In `--pick` mode, remember the chosen paths and exit.
+    pub fn pick_selection(&mut self) {
+        self.picked = self.marked_or_selected_paths();
+        if !self.picked.is_empty() {
+            self.quit();
+        }
+    }
+
     pub fn open_file(&mut self) {
         use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
         use std::process::Command;
 
-        if let Some(file_index) = self.list_state.selected() {
-            let entries = self.dir.entries();
-            if let Some(selected_entry) = entries.get(file_index)
-            // && !selected_entry.ends_with('/')
-            {
-                let full_path = format!("{}/{}", self.dir.path, selected_entry);
+        if let Some(file_index) = self.list_state.selected()
+            && let Some(selected_entry) = self.dir.entry_name(file_index)
+        // && !selected_entry.ends_with('/')
+        {
+            let full_path = format!("{}/{}", self.dir.path, selected_entry);
 
-                // leave TUI mode (temporarily)
-                if let Err(e) = disable_raw_mode() {
-                    eprintln!("Failed to disable raw mode: {e}");
-                }
+            // leave TUI mode (temporarily)
+            if let Err(e) = disable_raw_mode() {
+                self.notify_error(format!("Failed to disable raw mode: {e}"));
+            }
 
-                #[cfg(target_os = "macos")]
-                let mut cmd = Command::new("open");
-                #[cfg(target_os = "linux")]
-                let mut cmd = Command::new("xdg-open");
-                #[cfg(target_os = "windows")]
-                let mut cmd = Command::new("cmd");
-
-                #[cfg(target_os = "windows")]
-                {
-                    cmd.args(["/C", "start", "", &full_path])
-                        .stdout(std::process::Stdio::null())
-                        .stderr(std::process::Stdio::null());
-                }
-                #[cfg(not(target_os = "windows"))]
-                {
-                    cmd.arg(&full_path)
-                        .stdout(std::process::Stdio::null())
-                        .stderr(std::process::Stdio::null());
-                }
+            #[cfg(target_os = "macos")]
+            let mut cmd = Command::new("open");
+            #[cfg(target_os = "linux")]
+            let mut cmd = Command::new("xdg-open");
+            #[cfg(target_os = "windows")]
+            let mut cmd = Command::new("cmd");
 
-                match cmd.status() {
-                    Ok(status) => {
-                        if !status.success() {
-                            // eprintln!("Failed to open file: {:?}", status);
-                        }
-                    }
-                    Err(_err) => {
-                        // eprintln!("Error launching file: {err}");
+            #[cfg(target_os = "windows")]
+            {
+                cmd.args(["/C", "start", "", &full_path])
+                    .stdout(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::null());
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                cmd.arg(&full_path)
+                    .stdout(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::null());
+            }
+
+            match cmd.status() {
+                Ok(status) => {
+                    if !status.success() {
+                        self.notify_error(format!("Failed to open {selected_entry} ({status})"));
                     }
                 }
-
-                if let Err(e) = enable_raw_mode() {
-                    eprintln!("Failed to enable raw mode: {e}");
+                Err(err) => {
+                    self.notify_error(format!("Error launching file: {err}"));
                 }
             }
+
+            if let Err(e) = enable_raw_mode() {
+                self.notify_error(format!("Failed to enable raw mode: {e}"));
+            }
         }
     }
 
     pub async fn delete_file(&mut self) {
-        if let Some(i) = self.list_state.selected() {
-            let entries = self.dir.entries();
-            if let Some(selected_entry) = entries.get(i) {
-                self.show_confirmation = true;
-                self.file_to_delete = Some(selected_entry.clone());
-            }
+        if let Some(i) = self.list_state.selected()
+            && let Some(selected_entry) = self.dir.entry_name(i)
+        {
+            self.show_confirmation = true;
+            self.file_to_delete = Some(selected_entry.clone());
         }
     }
 
     pub fn rename_file(&mut self) {
-        if let Some(i) = self.list_state.selected() {
-            let entries = self.dir.entries();
-            if let Some(selected_entry) = entries.get(i) {
-                self.show_rename = true;
-                self.file_to_rename = Some(selected_entry.to_string());
-
-                // Pre-populate the input with the current filename
-                let current_name = selected_entry.trim_end_matches('/');
-                self.rename_input = TextArea::from([current_name]);
-                self.rename_input
-                    .set_block(Block::bordered().title("New name"));
+        if let Some(i) = self.list_state.selected()
+            && let Some(selected_entry) = self.dir.entry_name(i)
+        {
+            self.show_rename = true;
+            self.file_to_rename = Some(selected_entry.to_string());
+
+            // Pre-populate the input with the current filename
+            let current_name = selected_entry.trim_end_matches('/');
+            self.rename_input = TextArea::from([current_name]);
+            self.rename_input
+                .set_block(Block::bordered().title("New name"));
+            self.rename_error = None;
+
+            // put the cursor before the extension so typing keeps it
+            let extension_start = current_name
+                .rfind('.')
+                .filter(|i| *i > 0 && !selected_entry.ends_with('/'));
+            match extension_start {
+                Some(i) => {
+                    let col = current_name[..i].chars().count() as u16;
+                    self.rename_input
+                        .move_cursor(tui_textarea::CursorMove::Jump(0, col));
+                }
+                None => self.rename_input.move_cursor(tui_textarea::CursorMove::End),
             }
         }
     }
@@ -84,116 +190,133 @@ impl App {
     pub fn yank_file(&mut self) {
         use std::process::Command;
 
-        if let Some(i) = self.list_state.selected() {
-            let entries = self.dir.entries();
-            if let Some(selected_entry) = entries.get(i) {
-                let full_path = format!("{}/{}", self.dir.path, selected_entry);
-
-                #[cfg(target_os = "macos")]
-                let mut cmd = Command::new("pbcopy");
-                #[cfg(target_os = "linux")]
-                let mut cmd = Command::new("xclip");
-                #[cfg(target_os = "windows")]
-                let mut cmd = Command::new("clip");
-
-                #[cfg(target_os = "linux")]
-                {
-                    cmd.args(["-selection", "clipboard"]);
-                }
+        if let Some(i) = self.list_state.selected()
+            && let Some(selected_entry) = self.dir.entry_name(i)
+        {
+            let full_path = format!("{}/{}", self.dir.path, selected_entry);
 
-                let mut process = cmd
-                    .stdin(std::process::Stdio::piped())
-                    .stdout(std::process::Stdio::null())
-                    .stderr(std::process::Stdio::null())
-                    .spawn()
-                    .expect("Failed to spawn clipboard command");
-
-                if let Some(stdin) = process.stdin.as_mut() {
-                    use std::io::Write;
-                    stdin
-                        .write_all(full_path.as_bytes())
-                        .expect("Failed to write to clipboard");
+            #[cfg(target_os = "macos")]
+            let mut cmd = Command::new("pbcopy");
+            #[cfg(target_os = "linux")]
+            let mut cmd = Command::new("xclip");
+            #[cfg(target_os = "windows")]
+            let mut cmd = Command::new("clip");
+
+            #[cfg(target_os = "linux")]
+            {
+                cmd.args(["-selection", "clipboard"]);
+            }
+
+            let mut process = match cmd
+                .stdin(std::process::Stdio::piped())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .spawn()
+            {
+                Ok(process) => process,
+                Err(err) => {
+                    self.notify_error(format!("Failed to spawn clipboard command: {err}"));
+                    return;
                 }
+            };
 
-                let _ = process.wait();
+            if let Some(stdin) = process.stdin.as_mut() {
+                use std::io::Write;
+                if let Err(err) = stdin.write_all(full_path.as_bytes()) {
+                    self.notify_error(format!("Failed to write to clipboard: {err}"));
+                }
             }
+
+            let _ = process.wait();
+            self.notify_info(format!("Copied {full_path} to the clipboard"));
         }
     }
 
     pub fn handle_copy_file(&mut self) {
-        if let Some(i) = self.list_state.selected() {
-            let entries = self.dir.entries();
-            if let Some(selected_entry) = entries.get(i) {
-                let full_path = format!("{}/{}", self.dir.path, selected_entry);
-                self.clipboard = Some(Clipboard {
-                    cut: false,
-                    path: full_path,
-                });
-            }
+        if let Some(i) = self.list_state.selected()
+            && let Some(selected_entry) = self.dir.entry_name(i)
+        {
+            let full_path = format!("{}/{}", self.dir.path, selected_entry);
+            self.clipboard = Some(Clipboard {
+                cut: false,
+                path: full_path,
+            });
         }
     }
 
     pub fn handle_cut_file(&mut self) {
-        if let Some(i) = self.list_state.selected() {
-            let entries = self.dir.entries();
-            if let Some(selected_entry) = entries.get(i) {
-                let full_path = format!("{}/{}", self.dir.path, selected_entry);
-                self.clipboard = Some(Clipboard {
-                    cut: true,
-                    path: full_path,
-                });
-            }
+        if let Some(i) = self.list_state.selected()
+            && let Some(selected_entry) = self.dir.entry_name(i)
+        {
+            let full_path = format!("{}/{}", self.dir.path, selected_entry);
+            self.clipboard = Some(Clipboard {
+                cut: true,
+                path: full_path,
+            });
         }
     }
 
     pub async fn handle_paste(&mut self) {
-        if let Some(clipboard_path) = &self.clipboard.as_ref().map(|c| &c.path) {
-            let filename = clipboard_path.rsplit('/').next().unwrap_or("pasted_file");
-            let new_path = format!("{}/{}", self.dir.path, filename);
-            if let Err(err) = fs::copy(clipboard_path, &new_path) {
-                eprintln!("Failed to paste file: {err}");
-            } else {
-                self.dir.scan_and_add(self.show_hidden_files).await.unwrap();
-            }
+        let Some(to_paste) = self.clipboard.take() else {
+            return;
+        };
+        let filename = to_paste.path.rsplit('/').next().unwrap_or("pasted_file");
+        let new_path = format!("{}/{}", self.dir.path, filename);
+        if let Err(err) = fs::copy(&to_paste.path, &new_path) {
+            // keep the original of a cut when the copy failed
+            self.notify_error(format!("Failed to paste {filename}: {err}"));
+            return;
         }
 
-        if let Some(to_cut) = &self.clipboard {
-            if to_cut.cut {
-                if let Err(_r) = fs::remove_file(&to_cut.path) {
-                    fs::remove_dir_all(&to_cut.path).unwrap_or_else(|err| {
-                        eprintln!("Failed to delete original directory after cut: {err}");
-                    });
-                }
-                self.dir.scan_and_add(self.show_hidden_files).await.unwrap();
-            }
-            self.clipboard = None;
+        if to_paste.cut
+            && let Err(_r) = fs::remove_file(&to_paste.path)
+            && let Err(err) = fs::remove_dir_all(&to_paste.path)
+        {
+            self.notify_error(format!(
+                "Failed to delete original directory after cut: {err}"
+            ));
         }
+        self.rescan();
     }
 
     pub fn new_file(&mut self) {
         self.show_new_file = true;
-        self.rename_input = TextArea::default();
-        self.rename_input
-            .set_block(Block::bordered().title("New name"));
+        self.new_directory = false;
+        self.new_file_input = TextArea::default();
+        self.new_file_input
+            .set_block(Block::bordered().title("New name (end with / for a directory)"));
     }
 
+    pub fn new_directory(&mut self) {
+        self.show_new_file = true;
+        self.new_directory = true;
+        self.new_file_input = TextArea::default();
+        self.new_file_input
+            .set_block(Block::bordered().title("New directory name"));
+    }
+
+    /// Create `name` (which may contain `/`) in the current directory, making
+    /// any missing parent directories. A trailing `/` makes it a directory.
     pub async fn handle_rename_input(&mut self, key: KeyEvent) -> Result<()> {
         match key.code {
             KeyCode::Enter => {
                 if let Some(old_name) = &self.file_to_rename {
                     let new_name = self.rename_input.lines().join("").trim().to_string();
-                    if !new_name.is_empty() {
-                        // Remove trailing slash if it's a directory
-                        let old_name_clean = old_name.trim_end_matches('/');
-                        let old_path = format!("{}/{}", self.dir.path, old_name_clean);
-                        let new_path = format!("{}/{}", self.dir.path, new_name);
-                        if let Err(err) = fs::rename(&old_path, &new_path) {
-                            eprintln!("Failed to rename file: {err}");
-                        } else {
-                            self.dir.scan_and_add(self.show_hidden_files).await.unwrap();
+                    // Remove trailing slash if it's a directory
+                    let old_name_clean = old_name.trim_end_matches('/').to_string();
+                    if new_name != old_name_clean {
+                        // keep the dialog open until the name is usable
+                        if let Err(err) =
+                            rename_in(Path::new(&self.dir.path), &old_name_clean, &new_name)
+                        {
+                            self.rename_error = Some(err);
+                            return Ok(());
                         }
+                        self.rescan();
+                        self.pending_selection = Some(new_name);
                     }
                 }
+                self.rename_error = None;
                 self.rename_input = TextArea::default();
                 self.rename_input
                     .set_block(Block::bordered().title("New name"));
@@ -206,10 +329,12 @@ impl App {
                     .set_block(Block::bordered().title("New name"));
                 self.show_rename = false;
                 self.file_to_rename = None;
+                self.rename_error = None;
             }
             _ => {
                 // Pass the event to the text area input
                 self.rename_input.input(Event::Key(key));
+                self.rename_error = None;
             }
         }
         Ok(())
@@ -220,11 +345,12 @@ impl App {
             KeyCode::Enter => {
                 let new_name = self.new_file_input.lines().join("").trim().to_string();
                 if !new_name.is_empty() {
-                    let new_path = format!("{}/{}", self.dir.path, new_name);
-                    if let Err(err) = fs::File::create(&new_path) {
-                        eprintln!("Failed to create file: {err}");
+                    let dir = Path::new(&self.dir.path);
+                    if let Err(err) = create_in(dir, &new_name, self.new_directory) {
+                        self.notify_error(format!("Failed to create {new_name}: {err}"));
                     } else {
-                        self.dir.scan_and_add(self.show_hidden_files).await.unwrap();
+                        self.rescan();
+                        self.pending_selection = created_entry(&new_name);
                     }
                 }
                 self.new_file_input = TextArea::default();
@@ -246,3 +372,111 @@ impl App {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn validate_name_accepts_plain_names() {
+        assert!(validate_name("notes.txt").is_ok());
+        assert!(validate_name(".hidden").is_ok());
+        assert!(validate_name("with space").is_ok());
+    }
+
+    #[test]
+    fn validate_name_rejects_bad_names() {
+        for name in ["", ".", "..", "a/b", "nul\0byte"] {
+            assert!(validate_name(name).is_err(), "{name:?} was accepted");
+        }
+        assert!(validate_name(&"x".repeat(MAX_NAME_LEN)).is_ok());
+        assert!(validate_name(&"x".repeat(MAX_NAME_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn rename_refuses_to_replace_another_file() {
+        let dir = temp_dir("rename-exists");
+        fs::write(dir.join("a"), "a").unwrap();
+        fs::write(dir.join("b"), "b").unwrap();
+        let result = rename_in(&dir, "a", "b");
+        let kept = fs::read_to_string(dir.join("b")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(result, Err("'b' already exists".to_string()));
+        assert_eq!(kept, "b");
+    }
+
+    #[test]
+    fn case_only_rename_refuses_a_different_file() {
+        let dir = temp_dir("rename-case");
+        fs::write(dir.join("readme"), "lower").unwrap();
+        let upper = dir.join("README");
+        fs::write(&upper, "upper").unwrap();
+        // only meaningful where both names can exist side by side
+        let case_sensitive = fs::read_to_string(dir.join("readme")).unwrap() == "lower";
+
+        let result = rename_in(&dir, "readme", "README");
+        let kept = fs::read_to_string(&upper).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        if case_sensitive {
+            assert_eq!(result, Err("'README' already exists".to_string()));
+            assert_eq!(kept, "upper");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rename_refuses_a_hard_link_to_the_same_file() {
+        let dir = temp_dir("rename-hard-link");
+        fs::write(dir.join("a"), "x").unwrap();
+        fs::hard_link(dir.join("a"), dir.join("b")).unwrap();
+        let result = rename_in(&dir, "a", "b");
+        let both_kept = dir.join("a").exists() && dir.join("b").exists();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(result, Err("'b' already exists".to_string()));
+        assert!(both_kept);
+    }
+
+    #[test]
+    fn create_refuses_paths_outside_the_directory() {
+        let dir = temp_dir("create-escape");
+        fs::create_dir(dir.join("inner")).unwrap();
+        let inner = dir.join("inner");
+        for name in ["/etc/x", "../x", "a/../../x"] {
+            let err = create_in(&inner, name, false).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput, "{name}");
+        }
+        let escaped = dir.join("x").exists() || inner.join("a").exists();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(!escaped);
+    }
+
+    #[test]
+    fn create_with_a_trailing_slash_makes_only_directories() {
+        let dir = temp_dir("create-nested");
+        let result = create_in(&dir, "a/b/c/", false);
+        let all_dirs = ["a", "a/b", "a/b/c"].iter().all(|p| dir.join(p).is_dir());
+        let only_c = fs::read_dir(dir.join("a/b/c")).unwrap().count() == 0;
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(result.is_ok(), "{result:?}");
+        assert!(all_dirs && only_c);
+    }
+
+    #[test]
+    fn created_entry_skips_current_dir_components() {
+        assert_eq!(created_entry("./x").as_deref(), Some("x"));
+        assert_eq!(created_entry("a/b/c/").as_deref(), Some("a"));
+        assert_eq!(created_entry("file").as_deref(), Some("file"));
+    }
+
+    #[test]
+    fn rename_to_a_free_name() {
+        let dir = temp_dir("rename-free");
+        fs::write(dir.join("old"), "x").unwrap();
+        let result = rename_in(&dir, "old", "new");
+        let moved = dir.join("new").exists() && !dir.join("old").exists();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(result, Ok(()));
+        assert!(moved);
+    }
+}
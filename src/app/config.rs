@@ -1,10 +1,100 @@
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct Cli {
+    /// Directory to open, or a file to open with it selected
+    pub path: Option<PathBuf>,
+
     #[arg(long)]
     pub show_hidden_files: bool,
+
+    /// File picker mode: Enter prints the marked (or selected) paths to stdout and exits
+    #[arg(long)]
+    pub pick: bool,
+
+    /// Layout of the main view
+    #[arg(long, value_enum, default_value_t = LayoutMode::Split)]
+    pub layout: LayoutMode,
+
+    /// Relative widths of the parent, current and preview columns in the miller layout
+    #[arg(
+        long,
+        value_name = "PARENT:CURRENT:PREVIEW",
+        default_value = "1:3:3",
+        value_parser = parse_column_ratios
+    )]
+    pub column_ratios: [u16; 3],
+
+    /// Write the last visited directory to FILE on exit (for cd-on-quit wrappers)
+    #[arg(long, value_name = "FILE", alias = "cd-on-quit")]
+    pub choosedir: Option<PathBuf>,
+
+    /// Write the marked (or selected) paths to FILE on exit, one per line
+    #[arg(long, value_name = "FILE")]
+    pub choosefiles: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Print a shell function that changes to the last visited directory on exit
+    ShellInit { shell: ShellKind },
+    /// Import the directories of an existing z or zoxide database into the frecency database
+    Import {
+        source: ImportSource,
+        /// Database file (z only; defaults to `$_Z_DATA` or `~/.z`)
+        file: Option<PathBuf>,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ImportSource {
+    Z,
+    Zoxide,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayoutMode {
+    /// Current directory and preview side by side
+    Split,
+    /// Parent directory, current directory and preview (ranger style)
+    Miller,
+}
+
+fn parse_column_ratios(s: &str) -> Result<[u16; 3], String> {
+    let parts: Vec<&str> = s.split(':').collect();
+    let [parent, current, preview] = parts.as_slice() else {
+        return Err("expected three ratios like 1:3:3".to_string());
+    };
+    let parse = |p: &str| {
+        p.trim()
+            .parse::<u16>()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| format!("'{p}' is not a positive number"))
+    };
+    Ok([parse(parent)?, parse(current)?, parse(preview)?])
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ShellKind {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl ShellKind {
+    pub fn wrapper(self) -> &'static str {
+        match self {
+            ShellKind::Bash => include_str!("../../shell/tfm.bash"),
+            ShellKind::Zsh => include_str!("../../shell/tfm.zsh"),
+            ShellKind::Fish => include_str!("../../shell/tfm.fish"),
+        }
+    }
 }
 
 pub fn load_config() -> Cli {
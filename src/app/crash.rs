@@ -0,0 +1,75 @@
+use super::storage;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::{env, fs};
+
+/// Number of recent actions kept for the crash report.
+const MAX_RECENT_ACTIONS: usize = 50;
+
+static RECENT_ACTIONS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Remember `action` for the crash report.
+pub fn record(action: String) {
+    // a panic while the lock was held shouldn't stop later reports
+    let mut recent = RECENT_ACTIONS.lock().unwrap_or_else(|e| e.into_inner());
+    if recent.len() == MAX_RECENT_ACTIONS {
+        recent.pop_front();
+    }
+    let time = chrono::Local::now().format("%H:%M:%S");
+    recent.push_back(format!("{time} {action}"));
+}
+
+/// Install a panic hook that, when the main thread panics, puts the terminal
+/// back into its normal state and writes a crash report before the previous
+/// hook prints the panic.
+pub fn install_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        // other threads (the blocking pool, output readers) die alone while the
+        // app keeps running, so leave the terminal be and just remember the panic
+        if std::thread::current().name() != Some("main") {
+            record(format!(
+                "panic on a worker thread: {}",
+                info.to_string().replace('\n', " ")
+            ));
+            return;
+        }
+        ratatui::restore();
+        match write_report(info) {
+            Ok(path) => eprintln!("A crash report was written to {}", path.display()),
+            Err(err) => eprintln!("Failed to write a crash report: {err}"),
+        }
+        previous(info);
+    }));
+}
+
+fn write_report(info: &PanicHookInfo) -> std::io::Result<PathBuf> {
+    let dir = storage::state_dir().ok_or_else(|| std::io::Error::other("no home directory"))?;
+    fs::create_dir_all(&dir)?;
+    let now = chrono::Local::now();
+    let path = dir.join(format!("crash-{}.log", now.format("%Y%m%d-%H%M%S")));
+
+    let mut report = String::new();
+    let _ = writeln!(report, "tui-file-manager {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "time: {}", now.to_rfc3339());
+    let _ = writeln!(report, "os: {} {}", env::consts::OS, env::consts::ARCH);
+    let thread = std::thread::current();
+    let _ = writeln!(report, "thread: {}", thread.name().unwrap_or("<unnamed>"));
+    let _ = writeln!(report, "\n{info}");
+
+    let _ = writeln!(report, "\nLast actions (oldest first):");
+    let recent = RECENT_ACTIONS.lock().unwrap_or_else(|e| e.into_inner());
+    for action in recent.iter() {
+        let _ = writeln!(report, "  {action}");
+    }
+    drop(recent);
+
+    let backtrace = std::backtrace::Backtrace::force_capture();
+    let _ = writeln!(report, "\nBacktrace:\n{backtrace}");
+
+    fs::write(&path, report)?;
+    Ok(path)
+}
@@ -0,0 +1,135 @@
+use super::*;
+use ratatui::widgets::StatefulWidget;
+
+/// A list of directory entries that only formats the rows currently on screen,
+/// so huge directories render as fast as small ones.
+pub struct EntryList<'a> {
+    dir: &'a Directory,
+    marked: Option<&'a HashSet<String>>,
+    block: Option<Block<'a>>,
+    style: Style,
+    highlight_style: Style,
+    highlight_symbol: &'a str,
+}
+
+impl<'a> EntryList<'a> {
+    pub fn new(dir: &'a Directory) -> Self {
+        Self {
+            dir,
+            marked: None,
+            block: None,
+            style: Style::default(),
+            highlight_style: Style::default(),
+            highlight_symbol: "",
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn marked(mut self, marked: &'a HashSet<String>) -> Self {
+        self.marked = Some(marked);
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+
+    pub fn highlight_symbol(mut self, symbol: &'a str) -> Self {
+        self.highlight_symbol = symbol;
+        self
+    }
+}
+
+impl StatefulWidget for EntryList<'_> {
+    type State = ListState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut ListState) {
+        buf.set_style(area, self.style);
+        let inner = match self.block {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+        if inner.is_empty() {
+            return;
+        }
+
+        let len = self.dir.len();
+        let height = inner.height as usize;
+
+        if len == 0 && !self.dir.readable {
+            let message = Paragraph::new(vec![
+                Line::from("🔒 Permission denied").red().bold(),
+                Line::from("You don't have permission to list this directory.").dark_gray(),
+            ])
+            .centered()
+            .wrap(ratatui::widgets::Wrap { trim: true });
+            message.render(inner, buf);
+            return;
+        }
+
+        // keep the selection on screen, scrolling as little as possible
+        let mut offset = state.offset().min(len.saturating_sub(height));
+        if let Some(selected) = state.selected() {
+            let selected = selected.min(len.saturating_sub(1));
+            if selected < offset {
+                offset = selected;
+            } else if selected >= offset + height {
+                offset = selected + 1 - height;
+            }
+        }
+        *state.offset_mut() = offset;
+
+        let symbol_width = self.highlight_symbol.chars().count() as u16;
+        let blank_symbol = " ".repeat(symbol_width as usize);
+        let text_x = inner.x + symbol_width;
+        let text_width = inner.width.saturating_sub(symbol_width);
+
+        for (row, index) in (offset..len.min(offset + height)).enumerate() {
+            let Some(entry) = self.dir.get(index) else {
+                break;
+            };
+            let y = inner.y + row as u16;
+            let is_selected = state.selected() == Some(index);
+            let is_marked = self
+                .marked
+                .is_some_and(|marked| !marked.is_empty() && marked.contains(&entry.label()));
+
+            let mut row_style = self.style;
+            if is_marked {
+                row_style = row_style.light_yellow();
+            }
+            if is_selected {
+                row_style = row_style.patch(self.highlight_style);
+            }
+            buf.set_style(Rect::new(inner.x, y, inner.width, 1), row_style);
+
+            let symbol = if is_selected {
+                self.highlight_symbol
+            } else {
+                &blank_symbol
+            };
+            buf.set_stringn(inner.x, y, symbol, symbol_width as usize, row_style);
+
+            let label = if is_marked {
+                format!("● {}", entry.display())
+            } else {
+                entry.display()
+            };
+            buf.set_stringn(text_x, y, label, text_width as usize, row_style);
+        }
+    }
+}
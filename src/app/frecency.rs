@@ -0,0 +1,381 @@
+use super::*;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const FRECENCY_FILE: &str = "frecency";
+/// Once the ranks add up to more than this, they are all scaled down so old
+/// entries age out (the same scheme z and zoxide use).
+const MAX_TOTAL_RANK: f64 = 10_000.0;
+/// Number of matches listed in the jump prompt.
+const MAX_MATCHES: usize = 20;
+/// How long visits are collected before the database is written.
+const SAVE_DELAY: Duration = Duration::from_secs(5);
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Visits {
+    rank: f64,
+    last_access: u64,
+}
+
+impl Visits {
+    /// Rank weighted by how recently the directory was visited.
+    fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_access);
+        let weight = match age {
+            a if a < 60 * 60 => 4.0,
+            a if a < 24 * 60 * 60 => 2.0,
+            a if a < 7 * 24 * 60 * 60 => 0.5,
+            _ => 0.25,
+        };
+        self.rank * weight
+    }
+}
+
+/// Visited directories ranked by frequency and recency.
+#[derive(Debug, Default)]
+pub struct Frecency {
+    entries: HashMap<String, Visits>,
+    /// When the oldest change that isn't on disk yet was made.
+    unsaved_since: Option<Instant>,
+}
+
+impl Frecency {
+    pub fn load() -> Self {
+        let entries = storage::load_pairs(FRECENCY_FILE)
+            .into_iter()
+            .filter_map(|(path, value)| {
+                let (rank, last_access) = value.split_once('\t')?;
+                let visits = Visits {
+                    rank: rank.parse().ok()?,
+                    last_access: last_access.parse().ok()?,
+                };
+                Some((path, visits))
+            })
+            .collect();
+        Self {
+            entries,
+            unsaved_since: None,
+        }
+    }
+
+    pub fn save(&mut self) -> std::io::Result<()> {
+        // a failed write is retried after the next change
+        self.unsaved_since = None;
+        let values: Vec<(&str, String)> = self
+            .entries
+            .iter()
+            .map(|(path, v)| (path.as_str(), format!("{}\t{}", v.rank, v.last_access)))
+            .collect();
+        storage::save_pairs(
+            FRECENCY_FILE,
+            values.iter().map(|(path, value)| (*path, value.as_str())),
+        )
+    }
+
+    /// Record a visit to `path`.
+    pub fn add(&mut self, path: &str) {
+        self.add_with(path, 1.0, now());
+    }
+
+    fn add_with(&mut self, path: &str, rank: f64, last_access: u64) {
+        self.unsaved_since.get_or_insert_with(Instant::now);
+        let visits = self.entries.entry(path.to_string()).or_insert(Visits {
+            rank: 0.0,
+            last_access,
+        });
+        visits.rank += rank;
+        visits.last_access = visits.last_access.max(last_access);
+
+        let total: f64 = self.entries.values().map(|v| v.rank).sum();
+        if total > MAX_TOTAL_RANK {
+            for visits in self.entries.values_mut() {
+                visits.rank *= 0.9;
+            }
+            self.entries.retain(|_, v| v.rank >= 1.0);
+        }
+    }
+
+    pub fn remove(&mut self, path: &str) {
+        self.unsaved_since.get_or_insert_with(Instant::now);
+        self.entries.remove(path);
+    }
+
+    pub fn is_saved(&self) -> bool {
+        self.unsaved_since.is_none()
+    }
+
+    /// Whether there are changes older than [`SAVE_DELAY`] to write.
+    pub fn save_due(&self) -> bool {
+        self.unsaved_since
+            .is_some_and(|since| since.elapsed() >= SAVE_DELAY)
+    }
+
+    /// Directories matching `query`, best first. Every word of the query has
+    /// to appear in the path in order, and the last one in the final component.
+    pub fn matches(&self, query: &str) -> Vec<&str> {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let now = now();
+        let mut found: Vec<(&str, f64)> = self
+            .entries
+            .iter()
+            .filter(|(path, _)| matches_terms(path, &terms))
+            .map(|(path, visits)| (path.as_str(), visits.score(now)))
+            .collect();
+        found.sort_by(|a, b| b.1.total_cmp(&a.1));
+        found.truncate(MAX_MATCHES);
+        found.into_iter().map(|(path, _)| path).collect()
+    }
+
+    /// Merge a z database (`path|rank|time` lines).
+    pub fn import_z(&mut self, contents: &str) -> usize {
+        let mut imported = 0;
+        for line in contents.lines() {
+            let mut fields = line.rsplitn(3, '|');
+            let (Some(time), Some(rank), Some(path)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            if let (Ok(rank), Ok(time)) = (rank.parse(), time.parse()) {
+                self.add_with(path, rank, time);
+                imported += 1;
+            }
+        }
+        imported
+    }
+
+    /// Merge the output of `zoxide query --list --score` (`score path` lines).
+    pub fn import_zoxide(&mut self, listing: &str) -> usize {
+        let now = now();
+        let mut imported = 0;
+        for line in listing.lines() {
+            let Some((score, path)) = line.trim_start().split_once(' ') else {
+                continue;
+            };
+            if let Ok(score) = score.parse() {
+                self.add_with(path.trim_start(), score, now);
+                imported += 1;
+            }
+        }
+        imported
+    }
+}
+
+fn matches_terms(path: &str, terms: &[String]) -> bool {
+    let path = path.to_lowercase();
+    let mut rest = path.as_str();
+    for term in terms {
+        match rest.find(term.as_str()) {
+            Some(found) => rest = &rest[found + term.len()..],
+            None => return false,
+        }
+    }
+    match terms.last() {
+        Some(last) => Path::new(&path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|name| name.contains(last.as_str())),
+        None => true,
+    }
+}
+
+/// Import an existing z or zoxide database into ours (the `import` subcommand).
+pub fn import(source: config::ImportSource, file: Option<&Path>) -> color_eyre::Result<usize> {
+    use color_eyre::eyre::eyre;
+
+    let mut frecency = Frecency::load();
+    let imported = match source {
+        config::ImportSource::Z => {
+            let file = match file {
+                Some(file) => file.to_path_buf(),
+                None => std::env::var_os("_Z_DATA")
+                    .map(Into::into)
+                    .or_else(|| std::env::var_os("HOME").map(|h| Path::new(&h).join(".z")))
+                    .ok_or_else(|| eyre!("no home directory"))?,
+            };
+            frecency.import_z(&fs::read_to_string(file)?)
+        }
+        config::ImportSource::Zoxide => {
+            let output = std::process::Command::new("zoxide")
+                .args(["query", "--list", "--score"])
+                .output()?;
+            if !output.status.success() {
+                return Err(eyre!("zoxide query failed"));
+            }
+            frecency.import_zoxide(&String::from_utf8_lossy(&output.stdout))
+        }
+    };
+    frecency.save()?;
+    Ok(imported)
+}
+
+impl App {
+    /// Count a visit to the current directory. The database is written in
+    /// batches by [`App::save_frecency_if_due`] and on exit.
+    pub fn record_visit(&mut self) {
+        self.frecency.add(&self.dir.path);
+    }
+
+    pub fn save_frecency_if_due(&mut self) {
+        if self.frecency.save_due()
+            && let Err(err) = self.frecency.save()
+        {
+            self.notify_error(format!("Failed to save frecency database: {err}"));
+        }
+    }
+
+    pub fn open_jump_prompt(&mut self) {
+        self.show_jump_prompt = true;
+        self.jump_input = TextArea::default();
+        self.jump_input
+            .set_block(Block::bordered().title("Jump to directory"));
+        self.jump_state.select(Some(0));
+    }
+
+    fn jump_matches(&self) -> Vec<String> {
+        let query = self.jump_input.lines().join(" ");
+        self.frecency
+            .matches(&query)
+            .into_iter()
+            .filter(|path| *path != self.dir.path)
+            .map(String::from)
+            .collect()
+    }
+
+    pub fn handle_jump_input(&mut self, key: KeyEvent) {
+        let matches = self.jump_matches();
+        match key.code {
+            KeyCode::Enter => {
+                self.show_jump_prompt = false;
+                let Some(path) = self.jump_state.selected().and_then(|i| matches.get(i)) else {
+                    return;
+                };
+                if Path::new(path).is_dir() {
+                    self.open_directory(history::directory_at(path));
+                } else {
+                    // gone since it was recorded
+                    self.frecency.remove(path);
+                }
+            }
+            KeyCode::Esc => {
+                self.show_jump_prompt = false;
+            }
+            KeyCode::Down if !matches.is_empty() => {
+                let i = self
+                    .jump_state
+                    .selected()
+                    .map_or(0, |i| (i + 1) % matches.len());
+                self.jump_state.select(Some(i));
+            }
+            KeyCode::Up if !matches.is_empty() => {
+                let i = self
+                    .jump_state
+                    .selected()
+                    .map_or(0, |i| (i + matches.len() - 1) % matches.len());
+                self.jump_state.select(Some(i));
+            }
+            _ => {
+                self.jump_input.input(Event::Key(key));
+                self.jump_state.select(Some(0));
+            }
+        }
+    }
+
+    pub fn render_jump_prompt(&mut self, frame: &mut Frame) {
+        use ratatui::layout::{Constraint, Layout};
+        use ratatui::style::Color;
+        use ratatui::widgets::{BorderType, Clear};
+
+        let area = centered_rect(60, 60, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::bordered()
+            .title(" ⚡ Jump ")
+            .title_style(Style::new().bold().cyan())
+            .border_type(BorderType::Rounded)
+            .border_style(Style::new().cyan())
+            .style(Style::new().bg(Color::Rgb(30, 30, 40)));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [input_area, list_area] =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(inner);
+        frame.render_widget(&self.jump_input, input_area);
+
+        let list = List::new(self.jump_matches())
+            .highlight_style(
+                Style::new()
+                    .bg(Color::Rgb(60, 60, 80))
+                    .fg(Color::Rgb(255, 215, 0))
+                    .bold(),
+            )
+            .highlight_symbol("▶ ");
+        frame.render_stateful_widget(list, list_area, &mut self.jump_state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(query: &str) -> Vec<String> {
+        query.split_whitespace().map(str::to_lowercase).collect()
+    }
+
+    #[test]
+    fn terms_must_appear_in_order() {
+        let path = "/home/me/Projects/tui-file-manager";
+        assert!(matches_terms(path, &terms("proj tui")));
+        assert!(matches_terms(path, &terms("PROJ Manager")));
+        assert!(!matches_terms(path, &terms("tui proj")));
+        assert!(matches_terms(path, &terms("")));
+    }
+
+    #[test]
+    fn last_term_must_be_in_the_last_component() {
+        let path = "/home/me/projects/website";
+        assert!(matches_terms(path, &terms("web")));
+        assert!(!matches_terms(path, &terms("projects")));
+        assert!(matches_terms(path, &terms("projects site")));
+    }
+
+    #[test]
+    fn import_z_keeps_pipes_in_paths() {
+        let mut frecency = Frecency::default();
+        let imported = frecency.import_z("/tmp/a|b|12.5|1600000000\nbroken line\n/x|nan?|1\n");
+        assert_eq!(imported, 1);
+        let visits = frecency.entries["/tmp/a|b"];
+        assert_eq!(visits.rank, 12.5);
+        assert_eq!(visits.last_access, 1_600_000_000);
+    }
+
+    #[test]
+    fn import_zoxide_keeps_spaces_in_paths() {
+        let mut frecency = Frecency::default();
+        let imported = frecency.import_zoxide("  42.0 /home/me/my dir\n   1.5 /srv\nnot a score\n");
+        assert_eq!(imported, 2);
+        assert_eq!(frecency.entries["/home/me/my dir"].rank, 42.0);
+        assert_eq!(frecency.entries["/srv"].rank, 1.5);
+    }
+
+    #[test]
+    fn ranks_age_once_the_total_is_too_high() {
+        let mut frecency = Frecency::default();
+        frecency.add_with("/big", MAX_TOTAL_RANK, 0);
+        frecency.add_with("/small", 1.0, 0);
+        // the total went over the limit: everything was scaled down and the
+        // entry that dropped below one visit was forgotten
+        assert_eq!(frecency.entries["/big"].rank, MAX_TOTAL_RANK * 0.9);
+        assert!(!frecency.entries.contains_key("/small"));
+
+        frecency.add_with("/small", 1.0, 0);
+        assert_eq!(frecency.entries["/small"].rank, 1.0);
+        assert_eq!(frecency.entries["/big"].rank, MAX_TOTAL_RANK * 0.9);
+    }
+}
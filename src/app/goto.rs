@@ -0,0 +1,236 @@
+use super::*;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Expand `~` and `$VAR` / `${VAR}` in `input`, resolving relative paths
+/// against `cwd`.
+pub fn expand_path(input: &str, cwd: &str) -> PathBuf {
+    let mut expanded = String::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            expanded.push(c);
+            continue;
+        }
+        let braced = chars.next_if_eq(&'{').is_some();
+        let mut name = String::new();
+        while let Some(&c) = chars.peek() {
+            if braced && c == '}' {
+                chars.next();
+                break;
+            }
+            if !(braced || c.is_ascii_alphanumeric() || c == '_') {
+                break;
+            }
+            name.push(c);
+            chars.next();
+        }
+        match env::var(&name) {
+            Ok(value) if !name.is_empty() => expanded.push_str(&value),
+            _ if name.is_empty() && !braced => expanded.push('$'),
+            _ => {}
+        }
+    }
+
+    let home = env::var("HOME").unwrap_or_default();
+    let path = if expanded == "~" {
+        PathBuf::from(home)
+    } else if let Some(rest) = expanded.strip_prefix("~/") {
+        Path::new(&home).join(rest)
+    } else {
+        PathBuf::from(expanded)
+    };
+    Path::new(cwd).join(path)
+}
+
+/// Longest common prefix of `names`.
+pub fn common_prefix<'a>(names: &[&'a str]) -> &'a str {
+    let Some(first) = names.first() else {
+        return "";
+    };
+    let mut len = first.len();
+    for name in &names[1..] {
+        len = first
+            .char_indices()
+            .zip(name.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((i, a), _)| i + a.len_utf8())
+            .min(len);
+    }
+    &first[..len]
+}
+
+impl App {
+    pub fn open_goto_prompt(&mut self) {
+        self.show_goto = true;
+        self.goto_input = TextArea::default();
+        self.set_goto_message(None);
+    }
+
+    /// Show `message` (an error or the completion candidates) in the prompt title.
+    fn set_goto_message(&mut self, message: Option<String>) {
+        let block = match message {
+            Some(message) => Block::bordered()
+                .title(format!("Go to: {message}"))
+                .border_style(Style::new().red()),
+            None => Block::bordered().title("Go to (Tab completes)"),
+        };
+        self.goto_input.set_block(block);
+    }
+
+    pub fn handle_goto_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.goto_entered_path(),
+            KeyCode::Esc => {
+                self.show_goto = false;
+            }
+            KeyCode::Tab => self.complete_goto_path(),
+            _ => {
+                self.goto_input.input(Event::Key(key));
+                self.set_goto_message(None);
+            }
+        }
+    }
+
+    fn goto_entered_path(&mut self) {
+        let input = self.goto_input.lines().join("");
+        let input = input.trim();
+        if input.is_empty() {
+            self.show_goto = false;
+            return;
+        }
+        let Ok(path) = expand_path(input, &self.dir.path).canonicalize() else {
+            self.set_goto_message(Some(format!("{input} does not exist")));
+            return;
+        };
+
+        // a file opens its directory with the file selected
+        let (dir, selection) = if path.is_dir() {
+            (path, None)
+        } else {
+            let name = path.file_name().and_then(|n| n.to_str()).map(String::from);
+            (path.parent().map(Path::to_path_buf).unwrap_or(path), name)
+        };
+        self.show_goto = false;
+        let dir = dir.to_string_lossy().to_string();
+        if dir != self.dir.path {
+            self.open_directory(history::directory_at(&dir));
+        }
+        if let Some(name) = selection {
+            if self.is_loading(ScanTarget::Current) {
+                self.pending_selection = Some(name);
+            } else {
+                self.select_entry(&name);
+            }
+        }
+    }
+
+    /// Complete the last path component to the directories it could name.
+    fn complete_goto_path(&mut self) {
+        let input = self.goto_input.lines().join("");
+        let (typed_dir, prefix) = match input.rfind('/') {
+            Some(i) => input.split_at(i + 1),
+            None => ("", input.as_str()),
+        };
+        let search_dir = if typed_dir.is_empty() {
+            PathBuf::from(&self.dir.path)
+        } else {
+            expand_path(typed_dir, &self.dir.path)
+        };
+
+        let Ok(entries) = fs::read_dir(&search_dir) else {
+            self.set_goto_message(Some(format!("{typed_dir} does not exist")));
+            return;
+        };
+        let mut names: Vec<String> = entries
+            .flatten()
+            .filter(|e| e.path().is_dir())
+            .filter_map(|e| e.file_name().into_string().ok())
+            .filter(|name| name.starts_with(prefix))
+            .filter(|name| prefix.starts_with('.') || !name.starts_with('.'))
+            .collect();
+        names.sort();
+
+        // list the candidates when the completion is ambiguous
+        let (completed, message) = match names.as_slice() {
+            [] => {
+                self.set_goto_message(Some("no matches".to_string()));
+                return;
+            }
+            [only] => (format!("{typed_dir}{only}/"), None),
+            _ => {
+                let refs: Vec<&str> = names.iter().map(String::as_str).collect();
+                let completed = format!("{typed_dir}{}", common_prefix(&refs));
+                (completed, Some(names.join("  ")))
+            }
+        };
+        self.goto_input = TextArea::from([completed]);
+        self.goto_input.move_cursor(tui_textarea::CursorMove::End);
+        self.set_goto_message(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn home() -> PathBuf {
+        PathBuf::from(env::var("HOME").unwrap())
+    }
+
+    #[test]
+    fn expands_the_home_directory() {
+        assert_eq!(expand_path("~", "/cwd"), home());
+        assert_eq!(expand_path("~/x", "/cwd"), home().join("x"));
+        // only a leading ~ means home
+        assert_eq!(expand_path("a/~", "/cwd"), PathBuf::from("/cwd/a/~"));
+    }
+
+    #[test]
+    fn expands_variables() {
+        assert_eq!(expand_path("$HOME/x", "/cwd"), home().join("x"));
+        assert_eq!(
+            expand_path("${HOME}x", "/cwd"),
+            PathBuf::from(format!("{}x", home().display()))
+        );
+        assert_eq!(expand_path("rel", "/cwd"), PathBuf::from("/cwd/rel"));
+    }
+
+    #[test]
+    fn unset_variables_expand_to_nothing() {
+        let unset = "TFM_TEST_SURELY_UNSET";
+        assert!(env::var(unset).is_err());
+        assert_eq!(
+            expand_path(&format!("a/${unset}/b"), "/cwd"),
+            PathBuf::from("/cwd/a//b")
+        );
+        assert_eq!(
+            expand_path(&format!("a${{{unset}}}b"), "/cwd"),
+            PathBuf::from("/cwd/ab")
+        );
+    }
+
+    #[test]
+    fn a_lone_dollar_is_kept() {
+        assert_eq!(expand_path("$", "/cwd"), PathBuf::from("/cwd/$"));
+        assert_eq!(expand_path("a$/b", "/cwd"), PathBuf::from("/cwd/a$/b"));
+        assert_eq!(expand_path("price$", "/cwd"), PathBuf::from("/cwd/price$"));
+    }
+
+    #[test]
+    fn common_prefix_of_names() {
+        assert_eq!(common_prefix(&[]), "");
+        assert_eq!(common_prefix(&["only"]), "only");
+        assert_eq!(common_prefix(&["docs", "downloads", "do"]), "do");
+        assert_eq!(common_prefix(&["abc", "xyz"]), "");
+    }
+
+    #[test]
+    fn common_prefix_ends_on_a_char_boundary() {
+        assert_eq!(common_prefix(&["ärger", "ärmel"]), "är");
+        assert_eq!(common_prefix(&["日本語", "日本人"]), "日本");
+        // same first byte, different characters
+        assert_eq!(common_prefix(&["é", "ê"]), "");
+    }
+}
@@ -0,0 +1,123 @@
+use super::*;
+use std::path::Path;
+
+/// Directory for a path from the history.
+pub fn directory_at(path: &str) -> Directory {
+    let name = Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("")
+        .to_string();
+    Directory::new(name, path.to_string())
+}
+
+impl App {
+    pub fn go_back(&mut self) {
+        if let Some(path) = self.history.pop() {
+            self.forward_history.push(self.dir.path.clone());
+            self.load_directory(directory_at(&path));
+        }
+    }
+
+    pub fn go_forward(&mut self) {
+        if let Some(path) = self.forward_history.pop() {
+            self.history.push(self.dir.path.clone());
+            self.load_directory(directory_at(&path));
+        }
+    }
+
+    /// Back history, the current directory and forward history, oldest first.
+    fn jump_list(&self) -> Vec<&str> {
+        self.history
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(self.dir.path.as_str()))
+            .chain(self.forward_history.iter().rev().map(String::as_str))
+            .collect()
+    }
+
+    pub fn open_jump_list(&mut self) {
+        self.show_jump_list = true;
+        self.jump_list_state.select(Some(self.history.len()));
+    }
+
+    /// Go back or forward to the `index`-th entry of the jump list.
+    fn jump_to(&mut self, index: usize) {
+        let current = self.history.len();
+        if index == current || index >= self.jump_list().len() {
+            return;
+        }
+        let mut target = self.dir.path.clone();
+        while self.history.len() > index {
+            self.forward_history.push(target);
+            target = self.history.pop().unwrap_or_default();
+        }
+        while self.history.len() < index {
+            self.history.push(target);
+            target = self.forward_history.pop().unwrap_or_default();
+        }
+        self.load_directory(directory_at(&target));
+    }
+
+    pub fn handle_jump_list_input(&mut self, key: KeyEvent) {
+        let len = self.jump_list().len();
+        let selected = self.jump_list_state.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Enter => {
+                self.show_jump_list = false;
+                self.jump_to(selected);
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.show_jump_list = false;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.jump_list_state.select(Some((selected + 1) % len));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.jump_list_state
+                    .select(Some((selected + len - 1) % len));
+            }
+            _ => {}
+        }
+    }
+
+    pub fn render_jump_list(&mut self, frame: &mut Frame) {
+        use ratatui::style::Color;
+        use ratatui::widgets::{BorderType, Clear, ListItem};
+
+        let area = centered_rect(60, 60, frame.area());
+        frame.render_widget(Clear, area);
+
+        let current = self.history.len();
+        let items: Vec<ListItem> = self
+            .jump_list()
+            .into_iter()
+            .enumerate()
+            .map(|(i, path)| {
+                if i == current {
+                    ListItem::new(format!("{path}  (current)")).cyan()
+                } else {
+                    ListItem::new(path.to_string())
+                }
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::bordered()
+                    .title(" 🕘 Jump List ")
+                    .title_style(Style::new().bold().cyan())
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::new().cyan())
+                    .style(Style::new().bg(Color::Rgb(30, 30, 40))),
+            )
+            .highlight_style(
+                Style::new()
+                    .bg(Color::Rgb(60, 60, 80))
+                    .fg(Color::Rgb(255, 215, 0))
+                    .bold(),
+            )
+            .highlight_symbol("▶ ");
+        frame.render_stateful_widget(list, area, &mut self.jump_list_state);
+    }
+}
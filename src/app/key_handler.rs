@@ -7,12 +7,12 @@ impl App {
                 KeyCode::Char('y') => {
                     if let Some(file) = &self.file_to_delete {
                         let full_path = format!("{}/{}", self.dir.path, file);
-                        if let Err(_e) = fs::remove_file(&full_path) {
-                            fs::remove_dir_all(&full_path).unwrap_or_else(|err| {
-                                eprintln!("Failed to delete directory: {err}");
-                            });
+                        if let Err(_e) = fs::remove_file(&full_path)
+                            && let Err(err) = fs::remove_dir_all(&full_path)
+                        {
+                            self.notify_error(format!("Failed to delete {file}: {err}"));
                         }
-                        self.dir.scan_and_add(self.show_hidden_files).await.unwrap();
+                        self.rescan();
                     }
                     self.show_confirmation = false;
                     self.file_to_delete = None;
@@ -26,33 +26,13 @@ impl App {
             return Ok(());
         }
 
-        match key.code {
-            KeyCode::Esc | KeyCode::Char('q') => self.quit(),
-            KeyCode::Down | KeyCode::Char('j') => {
-                self.select_next();
-                self.update_subdir_preview_async().await;
-            }
-            KeyCode::Up | KeyCode::Char('k') => {
-                self.select_previous();
-                self.update_subdir_preview_async().await;
-            }
-            KeyCode::Right | KeyCode::Char('l') => self.enter_directory().await?,
-            KeyCode::Left | KeyCode::Char('h') => self.go_to_parent().await?,
-            KeyCode::Enter => self.open_file(),
-            KeyCode::Delete | KeyCode::Char('d') | KeyCode::Backspace => self.delete_file().await,
-            KeyCode::Char('r') => self.rename_file(),
-            KeyCode::Char('y') => self.yank_file(),
-            KeyCode::Char('a') => self.new_file(),
-            KeyCode::Char('c') => {
-                self.handle_copy_file();
-            }
-            KeyCode::Char('x') => {
-                self.handle_cut_file();
-            }
-            KeyCode::Char('p') => {
-                self.handle_paste().await;
-            }
-            _ => {}
+        if let Some(pending) = self.pending_key.take() {
+            self.handle_pending_key(pending, key);
+            return Ok(());
+        }
+
+        if let Some(action) = Action::from_key(key) {
+            self.perform(action).await?;
         }
         Ok(())
     }
@@ -0,0 +1,60 @@
+use super::*;
+use crate::file_ops::ScannedEntry;
+use crossterm::event::EventStream;
+use futures::StreamExt;
+use scan::ScanTarget;
+
+/// Messages any subsystem can post to the main loop through [`App::messages`].
+#[derive(Debug)]
+pub enum AppMessage {
+    /// Something outside the input handlers changed and the screen should be redrawn.
+    Redraw,
+    /// Run an action as if its key had been pressed.
+    Action(Action),
+    /// A change reported by the filesystem watcher.
+    FsEvent(notify::Event),
+    /// More entries from a background directory scan.
+    ScanBatch {
+        target: ScanTarget,
+        id: u64,
+        entries: Vec<ScannedEntry>,
+    },
+    /// A background directory scan finished (or failed).
+    ScanDone {
+        target: ScanTarget,
+        id: u64,
+        error: Option<String>,
+    },
+}
+
+/// Next terminal event, or never if input is currently detached (while suspended).
+pub async fn next_input(events: &mut Option<EventStream>) -> Option<std::io::Result<Event>> {
+    match events {
+        Some(events) => events.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+impl App {
+    pub async fn handle_message(&mut self, message: AppMessage) -> Result<()> {
+        match message {
+            AppMessage::Redraw => {}
+            AppMessage::Action(action) => self.perform(action).await?,
+            AppMessage::FsEvent(event) => {
+                let subdir = self.subdir.as_ref().map(|s| s.path.as_str());
+                if let Some(watcher) = &mut self.watcher {
+                    watcher.record(event, &self.dir.path, subdir);
+                }
+            }
+            AppMessage::ScanBatch {
+                target,
+                id,
+                entries,
+            } => self.on_scan_batch(target, id, entries),
+            AppMessage::ScanDone { target, id, error } => {
+                self.on_scan_done(target, id, error).await
+            }
+        }
+        Ok(())
+    }
+}
@@ -2,10 +2,10 @@ use super::*;
 
 impl App {
     pub fn select_next(&mut self) {
-        let items_len = self.dir.entries().len();
-        if items_len == 0 {
+        if self.dir.is_empty() {
             return;
         }
+        let items_len = self.dir.len();
         let i = match self.list_state.selected() {
             Some(i) => {
                 // in case we reach the botton, go to top
@@ -17,10 +17,10 @@ impl App {
     }
 
     pub fn select_previous(&mut self) {
-        let items_len = self.dir.entries().len();
-        if items_len == 0 {
+        if self.dir.is_empty() {
             return;
         }
+        let items_len = self.dir.len();
         let i = match self.list_state.selected() {
             Some(i) => {
                 // in case we reach the top, go to the bottom
@@ -31,28 +31,20 @@ impl App {
         self.list_state.select(Some(i));
     }
 
+    /// Select the entry with the given name, ignoring a trailing `/`.
+    pub fn select_entry(&mut self, name: &str) {
+        if let Some(i) = self.dir.position(name) {
+            self.list_state.select(Some(i));
+        }
+    }
+
     pub async fn enter_directory(&mut self) -> Result<()> {
-        if let Some(i) = self.list_state.selected() {
-            let entries = self.dir.entries();
-            if let Some(selected_entry) = entries.get(i) {
-                // Check if it's a directory (ends with '/')
-                if selected_entry.ends_with('/') {
-                    let dir_name = selected_entry.trim_end_matches('/');
-                    // Find the subdirectory and navigate into it
-                    if let Some(subdir) =
-                        self.dir.subdirectories.iter().find(|d| d.name == dir_name)
-                    {
-                        let new_path = subdir.path.clone();
-                        let new_name = subdir.name.clone();
-                        self.dir = Directory::new(new_name, new_path);
-
-                        // Scan the new directory
-                        self.dir.scan_and_add(self.show_hidden_files).await.unwrap();
-                        self.list_state.select(Some(0));
-                        self.update_subdir_preview_async().await;
-                    }
-                }
-            }
+        if let Some(i) = self.list_state.selected()
+            && let Some(Entry::Dir(subdir)) = self.dir.get(i)
+        {
+            let mut dir = Directory::new(subdir.name.clone(), subdir.path.clone());
+            dir.readable = subdir.readable;
+            self.open_directory(dir);
         }
         Ok(())
     }
@@ -70,40 +62,119 @@ impl App {
                 .unwrap_or("")
                 .to_string();
 
-            self.dir = Directory::new(parent_name, parent_str.to_string());
-
-            // Scan the parent directory
-            self.dir.scan_and_add(self.show_hidden_files).await.unwrap();
-            self.list_state.select(Some(0));
-            self.update_subdir_preview_async().await;
+            let came_from = self.dir.name.clone();
+            self.open_directory(Directory::new(parent_name, parent_str.to_string()));
+            // land on the directory we came from
+            self.pending_selection = Some(came_from);
         }
         Ok(())
     }
 
-    pub async fn update_subdir_preview_async(&mut self) {
-        if let Some(i) = self.list_state.selected() {
-            let entries = self.dir.entries();
-            if let Some(selected_entry) = entries.get(i)
-                && selected_entry.ends_with('/')
-            {
-                let dir_name = selected_entry.trim_end_matches('/');
-
-                if let Some(subdir) = self.dir.subdirectories.iter().find(|d| d.name == dir_name) {
-                    let mut preview_dir = Directory::new(subdir.name.clone(), subdir.path.clone());
-                    // Scan asynchronously
-                    if preview_dir
-                        .scan_and_add(self.show_hidden_files)
-                        .await
-                        .is_ok()
-                    {
-                        self.subdir = Some(preview_dir);
-                    } else {
-                        self.subdir = None;
-                    }
-                    return;
-                }
+    /// Leave the current directory for `dir`, recording it in the history.
+    pub fn open_directory(&mut self, dir: Directory) {
+        self.history.push(self.dir.path.clone());
+        self.forward_history.clear();
+        self.load_directory(dir);
+        self.record_visit();
+    }
+
+    /// Show `dir`, restoring the selection and scroll offset it had when we
+    /// last left it.
+    pub fn load_directory(&mut self, dir: Directory) {
+        self.remember_position();
+        self.dir = dir;
+        self.marked.clear();
+
+        self.list_state.select(Some(0));
+        *self.list_state.offset_mut() = 0;
+        match self.positions.get(&self.dir.path) {
+            Some((name, offset)) => {
+                self.pending_selection = Some(name.clone());
+                self.pending_offset = Some(*offset);
+            }
+            None => {
+                self.pending_selection = None;
+                self.pending_offset = None;
             }
         }
+
+        // Scan the new directory in the background
         self.subdir = None;
+        self.start_scan(ScanTarget::Current);
+        self.update_parent_listing();
+    }
+
+    /// Remember the selected entry and scroll offset of the current directory.
+    fn remember_position(&mut self) {
+        if let Some(name) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.dir.entry_name(i))
+        {
+            self.positions
+                .insert(self.dir.path.clone(), (name, self.list_state.offset()));
+        }
+    }
+
+    /// Keep the parent column in sync with the current directory (miller layout only).
+    pub fn update_parent_listing(&mut self) {
+        use std::path::Path;
+
+        if self.layout != LayoutMode::Miller {
+            self.parent_dir = None;
+            self.parent_scan = None;
+            return;
+        }
+        let Some(parent) = Path::new(&self.dir.path).parent() else {
+            self.parent_dir = None;
+            return;
+        };
+        let parent_path = parent.to_string_lossy().to_string();
+        if self
+            .parent_dir
+            .as_ref()
+            .is_some_and(|p| p.path == parent_path)
+        {
+            return;
+        }
+        let parent_name = parent
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("/")
+            .to_string();
+        self.parent_dir = Some(Directory::new(parent_name, parent_path));
+        self.start_scan(ScanTarget::Parent);
+    }
+
+    pub fn toggle_layout(&mut self) {
+        self.layout = match self.layout {
+            LayoutMode::Split => LayoutMode::Miller,
+            LayoutMode::Miller => LayoutMode::Split,
+        };
+        self.update_parent_listing();
+    }
+
+    pub async fn update_subdir_preview_async(&mut self) {
+        let preview = match self.list_state.selected().and_then(|i| self.dir.get(i)) {
+            Some(Entry::Dir(subdir)) => Some(subdir),
+            _ => None,
+        };
+
+        match preview {
+            // already showing (or loading) this directory
+            Some(subdir) if self.subdir.as_ref().is_some_and(|s| s.path == subdir.path) => {}
+            Some(subdir) => {
+                let mut preview = Directory::new(subdir.name.clone(), subdir.path.clone());
+                preview.readable = subdir.readable;
+                self.subdir = Some(preview);
+                // Scan in the background; this cancels any previous preview scan
+                self.start_scan(ScanTarget::Preview);
+            }
+            None => {
+                self.subdir = None;
+                self.preview_scan = None;
+            }
+        }
+        self.sync_watches();
     }
 }
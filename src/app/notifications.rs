@@ -0,0 +1,172 @@
+use super::*;
+use chrono::{DateTime, Local};
+use ratatui::style::Color;
+
+/// How long a notification stays on screen as a toast.
+const TOAST_DURATION: chrono::TimeDelta = chrono::TimeDelta::seconds(4);
+/// Most toasts shown at once; older ones are only in the message log.
+const MAX_TOASTS: usize = 3;
+/// Number of messages kept in the log.
+const MESSAGE_LOG_LIMIT: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    fn color(self) -> Color {
+        match self {
+            Level::Info => Color::Cyan,
+            Level::Warn => Color::Yellow,
+            Level::Error => Color::Red,
+        }
+    }
+
+    fn icon(self) -> &'static str {
+        match self {
+            Level::Info => "ℹ",
+            Level::Warn => "⚠",
+            Level::Error => "✗",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Notification {
+    pub level: Level,
+    pub text: String,
+    pub time: DateTime<Local>,
+}
+
+impl App {
+    pub fn notify(&mut self, level: Level, text: impl Into<String>) {
+        self.notifications.push(Notification {
+            level,
+            text: text.into(),
+            time: Local::now(),
+        });
+        if self.notifications.len() > MESSAGE_LOG_LIMIT {
+            let excess = self.notifications.len() - MESSAGE_LOG_LIMIT;
+            self.notifications.drain(..excess);
+        }
+    }
+
+    pub fn notify_info(&mut self, text: impl Into<String>) {
+        self.notify(Level::Info, text);
+    }
+
+    pub fn notify_warn(&mut self, text: impl Into<String>) {
+        self.notify(Level::Warn, text);
+    }
+
+    pub fn notify_error(&mut self, text: impl Into<String>) {
+        self.notify(Level::Error, text);
+    }
+
+    /// Notifications young enough to be shown as toasts, oldest first.
+    fn toasts(&self) -> impl Iterator<Item = &Notification> {
+        let now = Local::now();
+        let fresh = self
+            .notifications
+            .iter()
+            .rev()
+            .take(MAX_TOASTS)
+            .take_while(move |n| now - n.time < TOAST_DURATION)
+            .count();
+        self.notifications[self.notifications.len() - fresh..].iter()
+    }
+
+    /// Whether the set of visible toasts changed since the last call, so the
+    /// screen needs a redraw.
+    pub fn toasts_changed(&mut self) -> bool {
+        let count = self.toasts().count();
+        let changed = count != self.visible_toasts;
+        self.visible_toasts = count;
+        changed
+    }
+
+    pub fn toggle_messages(&mut self) {
+        self.show_messages = !self.show_messages;
+    }
+
+    /// Toasts stacked above the status bar in the bottom-right corner.
+    pub fn render_toasts(&self, frame: &mut Frame) {
+        use ratatui::widgets::{BorderType, Clear, Wrap};
+
+        let area = frame.area();
+        let width = (area.width / 3).max(30).min(area.width);
+        let mut bottom = area.height.saturating_sub(3);
+        let toasts: Vec<&Notification> = self.toasts().collect();
+        for toast in toasts.into_iter().rev() {
+            let text_width = width.saturating_sub(4).max(1) as usize;
+            let height = (toast.text.chars().count() / text_width + 3) as u16;
+            if bottom < height {
+                break;
+            }
+            let rect = Rect {
+                x: area.width - width,
+                y: bottom - height,
+                width,
+                height,
+            };
+            bottom -= height;
+
+            let color = toast.level.color();
+            frame.render_widget(Clear, rect);
+            frame.render_widget(
+                Paragraph::new(format!("{} {}", toast.level.icon(), toast.text))
+                    .wrap(Wrap { trim: true })
+                    .block(
+                        Block::bordered()
+                            .border_type(BorderType::Rounded)
+                            .border_style(Style::new().fg(color)),
+                    )
+                    .style(Style::new().bg(Color::Rgb(30, 30, 40)).fg(color)),
+                rect,
+            );
+        }
+    }
+
+    /// Every notification of this session, newest at the bottom.
+    pub fn render_messages(&self, frame: &mut Frame) {
+        use ratatui::widgets::{BorderType, Clear, Wrap};
+
+        let area = centered_rect(80, 70, frame.area());
+        frame.render_widget(Clear, area);
+        let lines: Vec<Line> = self
+            .notifications
+            .iter()
+            .map(|n| {
+                Line::from(vec![
+                    n.time.format("%H:%M:%S ").to_string().dark_gray(),
+                    format!("{} {}", n.level.icon(), n.text).fg(n.level.color()),
+                ])
+            })
+            .collect();
+        // keep the newest messages in view
+        let inner_height = area.height.saturating_sub(2) as usize;
+        let scroll = lines.len().saturating_sub(inner_height) as u16;
+        let text = if lines.is_empty() {
+            Text::from("No messages yet".dark_gray())
+        } else {
+            Text::from(lines)
+        };
+        frame.render_widget(
+            Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .scroll((scroll, 0))
+                .block(
+                    Block::bordered()
+                        .title(" 📜 Messages (E to close) ")
+                        .title_style(Style::new().bold().cyan())
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::new().cyan()),
+                )
+                .style(Style::new().bg(Color::Rgb(30, 30, 40))),
+            area,
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use super::*;
+use std::path::Path;
+
+impl App {
+    /// Turn the dual-pane mode on or off. The second pane opens on the
+    /// current directory.
+    pub fn toggle_dual_pane(&mut self) {
+        if self.other_pane.take().is_some() {
+            self.other_scan = None;
+            self.focus_right = false;
+            return;
+        }
+        let mut pane = Tab::new(Directory::new(self.dir.name.clone(), self.dir.path.clone()));
+        pane.sort_key = self.sort_key;
+        self.other_pane = Some(pane);
+        self.start_scan(ScanTarget::OtherPane);
+    }
+
+    /// Move the focus to the other pane.
+    pub fn switch_pane(&mut self) {
+        let Some(mut pane) = self.other_pane.take() else {
+            return;
+        };
+        self.current_scan = None;
+        self.preview_scan = None;
+        self.parent_scan = None;
+        self.swap_state(&mut pane);
+        self.other_pane = Some(pane);
+        self.focus_right = !self.focus_right;
+
+        self.reload_active();
+        self.start_scan(ScanTarget::OtherPane);
+    }
+
+    /// Copy (or move) the marked entries, or the selected one, from the
+    /// active pane into the directory of the other pane.
+    pub async fn transfer_to_other_pane(&mut self, remove_source: bool) {
+        let Some(target_dir) = self.other_pane.as_ref().map(|p| p.dir.path.clone()) else {
+            return;
+        };
+        if target_dir == self.dir.path {
+            return;
+        }
+        let sources = self.marked_or_selected_paths();
+
+        // returns how many entries were transferred and what went wrong
+        let result = tokio::task::spawn_blocking(move || {
+            let mut done = 0;
+            let mut errors = Vec::new();
+            for source in sources {
+                let source = Path::new(&source);
+                let Some(name) = source.file_name() else {
+                    continue;
+                };
+                let target = Path::new(&target_dir).join(name);
+                if target.exists() {
+                    errors.push(format!("{} already exists", target.display()));
+                    continue;
+                }
+                let result = if remove_source {
+                    file_ops::move_path(source, &target)
+                } else {
+                    file_ops::copy_recursive(source, &target)
+                };
+                match result {
+                    Ok(()) => done += 1,
+                    Err(err) => {
+                        errors.push(format!("Failed to transfer {}: {err}", source.display()))
+                    }
+                }
+            }
+            (done, errors)
+        })
+        .await;
+        match result {
+            Ok((done, errors)) => {
+                for err in errors {
+                    self.notify_error(err);
+                }
+                if done > 0 {
+                    let verb = if remove_source { "Moved" } else { "Copied" };
+                    self.notify_info(format!("{verb} {done} entries"));
+                }
+            }
+            Err(err) => self.notify_error(format!("Transfer failed: {err}")),
+        }
+
+        self.marked.clear();
+        if remove_source {
+            self.reload_active();
+        }
+        self.start_scan(ScanTarget::OtherPane);
+    }
+}
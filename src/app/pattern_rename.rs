@@ -0,0 +1,337 @@
+use super::*;
+use regex::Regex;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Case conversion applied to the renamed names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaseMode {
+    #[default]
+    Keep,
+    Lower,
+    Upper,
+    Title,
+}
+
+impl CaseMode {
+    fn next(self) -> Self {
+        match self {
+            CaseMode::Keep => CaseMode::Lower,
+            CaseMode::Lower => CaseMode::Upper,
+            CaseMode::Upper => CaseMode::Title,
+            CaseMode::Title => CaseMode::Keep,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            CaseMode::Keep => "keep",
+            CaseMode::Lower => "lower",
+            CaseMode::Upper => "UPPER",
+            CaseMode::Title => "Title",
+        }
+    }
+
+    fn apply(self, name: &str) -> String {
+        match self {
+            CaseMode::Keep => name.to_string(),
+            CaseMode::Lower => name.to_lowercase(),
+            CaseMode::Upper => name.to_uppercase(),
+            CaseMode::Title => {
+                let mut start_of_word = true;
+                name.chars()
+                    .map(|c| {
+                        let c = if start_of_word {
+                            c.to_uppercase().next().unwrap_or(c)
+                        } else {
+                            c.to_lowercase().next().unwrap_or(c)
+                        };
+                        start_of_word = !c.is_alphanumeric();
+                        c
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Expand `{n}` / `{n:WIDTH}` (counter) and `{date}` / `{date:FORMAT}`
+/// (modification time) in a replacement template, or say why a date format
+/// is invalid.
+fn expand_tokens(
+    template: &str,
+    counter: usize,
+    mtime: Option<SystemTime>,
+) -> Result<String, String> {
+    use chrono::format::{Item, StrftimeItems};
+
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let token = &rest[start + 1..start + len];
+        let (name, arg) = match token.split_once(':') {
+            Some((name, arg)) => (name, Some(arg)),
+            None => (token, None),
+        };
+        match name {
+            "n" => {
+                let width = arg.and_then(|w| w.parse().ok()).unwrap_or(0);
+                out.push_str(&format!("{counter:0width$}"));
+            }
+            "date" => {
+                // formatting panics on an invalid specifier, so check it first
+                let format = arg.unwrap_or("%Y-%m-%d");
+                if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                    return Err(format!("invalid date format {format:?}"));
+                }
+                let date: chrono::DateTime<chrono::Local> =
+                    mtime.unwrap_or(SystemTime::UNIX_EPOCH).into();
+                out.push_str(&date.format(format).to_string());
+            }
+            // not one of ours, keep it as typed
+            _ => out.push_str(&rest[start..=start + len]),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// State of the pattern rename dialog.
+#[derive(Debug)]
+pub struct PatternRename {
+    pub find: TextArea<'static>,
+    pub replace: TextArea<'static>,
+    pub editing_replace: bool,
+    pub case: CaseMode,
+    names: Vec<String>,
+    mtimes: Vec<Option<SystemTime>>,
+    existing: HashSet<String>,
+}
+
+impl PatternRename {
+    fn new(dir: &str, names: Vec<String>, existing: HashSet<String>) -> Self {
+        let mtimes = names
+            .iter()
+            .map(|name| fs::metadata(Path::new(dir).join(name)).and_then(|m| m.modified()))
+            .map(Result::ok)
+            .collect();
+        let mut dialog = Self {
+            find: TextArea::default(),
+            replace: TextArea::default(),
+            editing_replace: false,
+            case: CaseMode::Keep,
+            names,
+            mtimes,
+            existing,
+        };
+        dialog.update_blocks();
+        dialog
+    }
+
+    /// Highlight the field being edited.
+    fn update_blocks(&mut self) {
+        let style = |active: bool| {
+            if active {
+                Style::new().yellow()
+            } else {
+                Style::new().dark_gray()
+            }
+        };
+        self.find.set_block(
+            Block::bordered()
+                .title("Find (regex, empty = whole name)")
+                .border_style(style(!self.editing_replace)),
+        );
+        self.replace.set_block(
+            Block::bordered()
+                .title("Replace ($1 groups, {n} / {n:3} counter, {date} / {date:%Y%m%d} mtime)")
+                .border_style(style(self.editing_replace)),
+        );
+    }
+
+    /// The renames the current rules produce, or why the find pattern is invalid.
+    pub fn plan(&self) -> Result<RenamePlan, String> {
+        let find = self.find.lines().join("");
+        let find = if find.is_empty() {
+            "^.*$"
+        } else {
+            find.as_str()
+        };
+        let regex = Regex::new(find).map_err(|err| err.to_string())?;
+        let template = self.replace.lines().join("");
+
+        // the counter only advances for names the pattern matches
+        let mut counter = 0;
+        let mut new = Vec::with_capacity(self.names.len());
+        for (name, mtime) in self.names.iter().zip(&self.mtimes) {
+            let renamed = if template.is_empty() || !regex.is_match(name) {
+                name.clone()
+            } else {
+                counter += 1;
+                let replacement = expand_tokens(&template, counter, *mtime)?;
+                regex.replace_all(name, replacement.as_str()).into_owned()
+            };
+            new.push(self.case.apply(&renamed));
+        }
+        Ok(RenamePlan::new(&self.names, &new, &self.existing))
+    }
+}
+
+impl App {
+    pub fn open_pattern_rename(&mut self) {
+        let names = self.bulk_rename_names();
+        if names.is_empty() {
+            return;
+        }
+        self.pattern_rename = Some(PatternRename::new(
+            &self.dir.path,
+            names,
+            self.existing_names(),
+        ));
+    }
+
+    pub async fn handle_pattern_rename_input(&mut self, key: KeyEvent) {
+        use crossterm::event::KeyModifiers;
+
+        let Some(dialog) = &mut self.pattern_rename else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.pattern_rename = None;
+            }
+            KeyCode::Enter => {
+                let Ok(plan) = dialog.plan() else {
+                    return;
+                };
+                if !plan.is_valid() || plan.renames.is_empty() {
+                    return;
+                }
+                self.pattern_rename = None;
+                self.apply_rename_plan(plan).await;
+            }
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                dialog.editing_replace = !dialog.editing_replace;
+                dialog.update_blocks();
+            }
+            KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                dialog.case = dialog.case.next();
+            }
+            _ => {
+                let field = if dialog.editing_replace {
+                    &mut dialog.replace
+                } else {
+                    &mut dialog.find
+                };
+                field.input(Event::Key(key));
+            }
+        }
+    }
+
+    pub fn render_pattern_rename(&self, frame: &mut Frame) {
+        use ratatui::layout::{Constraint, Layout};
+        use ratatui::style::Color;
+        use ratatui::widgets::{BorderType, Clear};
+
+        let Some(dialog) = &self.pattern_rename else {
+            return;
+        };
+        let area = centered_rect(80, 80, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::bordered()
+            .title(" ✏️  Pattern Rename ")
+            .title_bottom(format!(
+                " Tab:Switch field │ Ctrl-T:Case ({}) │ Enter:Apply │ Esc:Cancel ",
+                dialog.case.label()
+            ))
+            .title_style(Style::new().bold().yellow())
+            .border_type(BorderType::Rounded)
+            .border_style(Style::new().yellow())
+            .style(Style::new().bg(Color::Rgb(30, 30, 40)));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [find_area, replace_area, preview_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(0),
+        ])
+        .areas(inner);
+        frame.render_widget(&dialog.find, find_area);
+        frame.render_widget(&dialog.replace, replace_area);
+
+        let lines: Vec<Line> = match dialog.plan() {
+            Err(err) => vec![Line::from(format!("✗ {err}")).red()],
+            Ok(plan) => {
+                let mut lines: Vec<Line> = plan
+                    .errors
+                    .iter()
+                    .map(|err| Line::from(format!("✗ {err}")).red())
+                    .collect();
+                for (old, new) in &plan.renames {
+                    lines.push(Line::from(vec![
+                        old.clone().into(),
+                        "  →  ".dark_gray(),
+                        new.clone().green(),
+                    ]));
+                }
+                if lines.is_empty() {
+                    lines.push(Line::from("No names change").dark_gray());
+                }
+                lines
+            }
+        };
+        frame.render_widget(Paragraph::new(lines), preview_area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// 2021-06-15, far enough from a year boundary for any time zone.
+    fn mid_2021() -> Option<SystemTime> {
+        Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_623_758_400))
+    }
+
+    #[test]
+    fn counter_is_zero_padded() {
+        assert_eq!(expand_tokens("img_{n}", 7, None).unwrap(), "img_7");
+        assert_eq!(expand_tokens("img_{n:3}", 7, None).unwrap(), "img_007");
+        assert_eq!(expand_tokens("{n:2}", 123, None).unwrap(), "123");
+    }
+
+    #[test]
+    fn default_date_is_iso() {
+        let expected = chrono::DateTime::<chrono::Local>::from(mid_2021().unwrap())
+            .format("%Y-%m-%d")
+            .to_string();
+        assert_eq!(expand_tokens("{date}", 1, mid_2021()).unwrap(), expected);
+    }
+
+    #[test]
+    fn custom_date_format() {
+        assert_eq!(
+            expand_tokens("{date:%Y}_$1", 1, mid_2021()).unwrap(),
+            "2021_$1"
+        );
+    }
+
+    #[test]
+    fn invalid_date_format_is_an_error() {
+        assert!(expand_tokens("{date:%}", 1, mid_2021()).is_err());
+        assert!(expand_tokens("{date:%Q}", 1, mid_2021()).is_err());
+    }
+
+    #[test]
+    fn unknown_tokens_are_kept() {
+        assert_eq!(expand_tokens("{x}{n", 1, None).unwrap(), "{x}{n");
+    }
+}
@@ -0,0 +1,921 @@
+use super::*;
+use ratatui::text::Span;
+use std::path::{Path, PathBuf};
+
+/// Rows of the checkbox grid and the mode bit of each column.
+const GRID: [(&str, [u32; 3]); 4] = [
+    ("User", [0o400, 0o200, 0o100]),
+    ("Group", [0o040, 0o020, 0o010]),
+    ("Other", [0o004, 0o002, 0o001]),
+    ("Special", [0o4000, 0o2000, 0o1000]),
+];
+/// Errors listed one by one before the rest are only counted.
+const MAX_REPORTED_ERRORS: usize = 5;
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::{CStr, CString};
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+    use std::{fs, io, path::Path};
+
+    pub fn mode(meta: &fs::Metadata) -> u32 {
+        meta.mode() & 0o7777
+    }
+
+    pub fn owner(meta: &fs::Metadata) -> (u32, u32) {
+        (meta.uid(), meta.gid())
+    }
+
+    pub fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))
+    }
+
+    pub fn set_owner(path: &Path, uid: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+        std::os::unix::fs::chown(path, uid, gid)
+    }
+
+    fn string_at(ptr: *const libc::c_char) -> String {
+        // SAFETY: the pointers come from entries libc just filled in
+        unsafe { CStr::from_ptr(ptr) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Run a reentrant `get*_r` lookup, growing the buffer while it's too small.
+    fn lookup<T, R>(
+        call: impl Fn(&mut T, &mut [libc::c_char], &mut *mut T) -> libc::c_int,
+        read: impl FnOnce(&T) -> R,
+    ) -> Option<R> {
+        let mut buf = vec![0; 1024];
+        loop {
+            // SAFETY: passwd and group are plain C structs
+            let mut entry: T = unsafe { std::mem::zeroed() };
+            let mut result = std::ptr::null_mut();
+            match call(&mut entry, &mut buf, &mut result) {
+                0 if !result.is_null() => return Some(read(&entry)),
+                libc::ERANGE if buf.len() < 1 << 20 => buf.resize(buf.len() * 2, 0),
+                _ => return None,
+            }
+        }
+    }
+
+    // These go through NSS, so users and groups from LDAP, SSSD and the like
+    // are found too.
+
+    pub fn user_id(name: &str) -> Option<u32> {
+        let name = CString::new(name).ok()?;
+        lookup(
+            |pwd, buf, result| unsafe {
+                libc::getpwnam_r(name.as_ptr(), pwd, buf.as_mut_ptr(), buf.len(), result)
+            },
+            |pwd: &libc::passwd| pwd.pw_uid,
+        )
+    }
+
+    pub fn group_id(name: &str) -> Option<u32> {
+        let name = CString::new(name).ok()?;
+        lookup(
+            |grp, buf, result| unsafe {
+                libc::getgrnam_r(name.as_ptr(), grp, buf.as_mut_ptr(), buf.len(), result)
+            },
+            |grp: &libc::group| grp.gr_gid,
+        )
+    }
+
+    pub fn user_name(uid: u32) -> Option<String> {
+        lookup(
+            |pwd, buf, result| unsafe {
+                libc::getpwuid_r(uid, pwd, buf.as_mut_ptr(), buf.len(), result)
+            },
+            |pwd: &libc::passwd| string_at(pwd.pw_name),
+        )
+    }
+
+    pub fn group_name(gid: u32) -> Option<String> {
+        lookup(
+            |grp, buf, result| unsafe {
+                libc::getgrgid_r(gid, grp, buf.as_mut_ptr(), buf.len(), result)
+            },
+            |grp: &libc::group| string_at(grp.gr_name),
+        )
+    }
+
+    /// Names of all users, for completion. `getpwent` keeps global state, so
+    /// only call this from the main thread.
+    pub fn user_names() -> Vec<String> {
+        let mut names = Vec::new();
+        unsafe {
+            libc::setpwent();
+            loop {
+                let pwd = libc::getpwent();
+                if pwd.is_null() {
+                    break;
+                }
+                names.push(string_at((*pwd).pw_name));
+            }
+            libc::endpwent();
+        }
+        names
+    }
+
+    /// Names of all groups, for completion. Main thread only, like
+    /// [`user_names`].
+    pub fn group_names() -> Vec<String> {
+        let mut names = Vec::new();
+        unsafe {
+            libc::setgrent();
+            loop {
+                let grp = libc::getgrent();
+                if grp.is_null() {
+                    break;
+                }
+                names.push(string_at((*grp).gr_name));
+            }
+            libc::endgrent();
+        }
+        names
+    }
+}
+
+#[cfg(not(unix))]
+mod sys {
+    use std::{fs, io, path::Path};
+
+    pub fn mode(_meta: &fs::Metadata) -> u32 {
+        0
+    }
+
+    pub fn owner(_meta: &fs::Metadata) -> (u32, u32) {
+        (0, 0)
+    }
+
+    pub fn set_mode(_path: &Path, _mode: u32) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    pub fn set_owner(_path: &Path, _uid: Option<u32>, _gid: Option<u32>) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    pub fn user_id(_name: &str) -> Option<u32> {
+        None
+    }
+
+    pub fn group_id(_name: &str) -> Option<u32> {
+        None
+    }
+
+    pub fn user_name(_uid: u32) -> Option<String> {
+        None
+    }
+
+    pub fn group_name(_gid: u32) -> Option<String> {
+        None
+    }
+
+    pub fn user_names() -> Vec<String> {
+        Vec::new()
+    }
+
+    pub fn group_names() -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Names sorted for completion, without the duplicates several NSS sources
+/// may return.
+fn sorted(mut names: Vec<String>) -> Vec<String> {
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Part of the dialog that has the focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Recursive,
+    Grid(usize),
+    Octal,
+    Owner,
+    Group,
+}
+
+impl Field {
+    const ORDER: [Field; 8] = [
+        Field::Recursive,
+        Field::Grid(0),
+        Field::Grid(1),
+        Field::Grid(2),
+        Field::Grid(3),
+        Field::Octal,
+        Field::Owner,
+        Field::Group,
+    ];
+
+    fn step(self, forward: bool) -> Field {
+        let len = Self::ORDER.len();
+        let i = Self::ORDER.iter().position(|f| *f == self).unwrap_or(0);
+        let i = if forward { i + 1 } else { i + len - 1 };
+        Self::ORDER[i % len]
+    }
+}
+
+/// Mode bits of a set of entries: those set in all of them and those set in
+/// any of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ModeBits {
+    all: u32,
+    any: u32,
+}
+
+impl ModeBits {
+    /// Without any entries every bit is unknown.
+    fn of(modes: impl IntoIterator<Item = u32>) -> Self {
+        let mut modes = modes.into_iter().peekable();
+        if modes.peek().is_none() {
+            return Self {
+                all: 0,
+                any: 0o7777,
+            };
+        }
+        modes.fold(
+            Self {
+                all: 0o7777,
+                any: 0,
+            },
+            |bits, mode| Self {
+                all: bits.all & mode,
+                any: bits.any | mode,
+            },
+        )
+    }
+}
+
+/// Bits the user turned on or off. The other bits keep each entry's own value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ModeEdit {
+    set: u32,
+    clear: u32,
+}
+
+impl ModeEdit {
+    /// Set every bit to its value in `mode`.
+    fn exactly(mode: u32) -> Self {
+        Self {
+            set: mode,
+            clear: !mode & 0o7777,
+        }
+    }
+
+    fn apply(self, mode: u32) -> u32 {
+        (mode & !self.clear) | self.set
+    }
+
+    /// Bits shown as on, and bits that still differ between the entries.
+    fn shown(self, bits: ModeBits) -> (u32, u32) {
+        let untouched = !(self.set | self.clear);
+        (self.apply(bits.all), (bits.any & !bits.all) & untouched)
+    }
+
+    /// Turn `bit` off if it's shown as on, else on.
+    fn toggle(&mut self, bit: u32, bits: ModeBits) {
+        if self.shown(bits).0 & bit != 0 {
+            self.set &= !bit;
+            self.clear |= bit;
+        } else {
+            self.clear &= !bit;
+            self.set |= bit;
+        }
+    }
+
+    /// The mode in octal, or `mixed` while the entries differ.
+    fn label(self, bits: ModeBits) -> String {
+        match self.shown(bits) {
+            (on, 0) => format!("{on:04o}"),
+            _ => "mixed".to_string(),
+        }
+    }
+}
+
+/// What applying the dialog changes.
+#[derive(Debug, Clone, Copy)]
+struct Changes {
+    edit: ModeEdit,
+    file_edit: ModeEdit,
+    recursive: bool,
+    uid: Option<u32>,
+    gid: Option<u32>,
+}
+
+impl Changes {
+    /// Apply to `path` and, when recursive, everything below it. Symlinks
+    /// met while recursing are left alone. Returns how many entries changed.
+    fn apply(&self, path: &Path, top_level: bool, errors: &mut Vec<String>) -> usize {
+        let meta = if top_level {
+            fs::metadata(path)
+        } else {
+            fs::symlink_metadata(path)
+        };
+        let meta = match meta {
+            Ok(meta) if !meta.file_type().is_symlink() => meta,
+            Ok(_) => return 0,
+            Err(err) => {
+                errors.push(format!("{}: {err}", path.display()));
+                return 0;
+            }
+        };
+
+        let mut changed = 0;
+        let chown = self.uid.is_some() || self.gid.is_some();
+        let edit = if self.recursive && !meta.is_dir() {
+            self.file_edit
+        } else {
+            self.edit
+        };
+        let old_mode = sys::mode(&meta);
+        let mode = edit.apply(old_mode);
+        // chown first: it may clear the setuid and setgid bits
+        let owned = if chown {
+            sys::set_owner(path, self.uid, self.gid)
+        } else {
+            Ok(())
+        };
+        let result = owned.and_then(|()| {
+            if mode != old_mode {
+                sys::set_mode(path, mode)
+            } else {
+                Ok(())
+            }
+        });
+        match result {
+            Ok(()) if chown || mode != old_mode => changed += 1,
+            Ok(()) => {}
+            Err(err) => errors.push(format!("{}: {err}", path.display())),
+        }
+
+        if self.recursive && meta.is_dir() {
+            match fs::read_dir(path) {
+                Ok(entries) => {
+                    for entry in entries.flatten() {
+                        changed += self.apply(&entry.path(), false, errors);
+                    }
+                }
+                Err(err) => errors.push(format!("{}: {err}", path.display())),
+            }
+        }
+        changed
+    }
+}
+
+/// State of the chmod / chown dialog.
+#[derive(Debug)]
+pub struct PermissionsDialog {
+    paths: Vec<PathBuf>,
+    /// Mode bits of all the entries, and of the directories and the files
+    /// among them.
+    entries: ModeBits,
+    dirs: ModeBits,
+    files: ModeBits,
+    /// Edits of all the entries, or of the directories when recursive.
+    edit: ModeEdit,
+    /// Edits of the files when recursive.
+    file_edit: ModeEdit,
+    recursive: bool,
+    editing_files: bool,
+    focus: Field,
+    column: usize,
+    octal: TextArea<'static>,
+    owner: TextArea<'static>,
+    group: TextArea<'static>,
+    initial_owner: String,
+    initial_group: String,
+    users: Vec<String>,
+    groups: Vec<String>,
+    /// An error (red) or the completion candidates.
+    message: Option<(String, bool)>,
+}
+
+impl PermissionsDialog {
+    fn new(paths: Vec<PathBuf>) -> std::io::Result<Self> {
+        let metas = paths
+            .iter()
+            .map(fs::metadata)
+            .collect::<std::io::Result<Vec<_>>>()?;
+        let bits = |dirs: Option<bool>| {
+            ModeBits::of(
+                metas
+                    .iter()
+                    .filter(|meta| dirs.is_none_or(|dirs| meta.is_dir() == dirs))
+                    .map(sys::mode),
+            )
+        };
+        let (uid, gid) = sys::owner(&metas[0]);
+        let users = sorted(sys::user_names());
+        let groups = sorted(sys::group_names());
+        let initial_owner = sys::user_name(uid).unwrap_or_else(|| uid.to_string());
+        let initial_group = sys::group_name(gid).unwrap_or_else(|| gid.to_string());
+
+        let mut dialog = Self {
+            paths,
+            entries: bits(None),
+            dirs: bits(Some(true)),
+            files: bits(Some(false)),
+            edit: ModeEdit::default(),
+            file_edit: ModeEdit::default(),
+            recursive: false,
+            editing_files: false,
+            focus: Field::Grid(0),
+            column: 0,
+            octal: TextArea::default(),
+            owner: TextArea::from([initial_owner.clone()]),
+            group: TextArea::from([initial_group.clone()]),
+            initial_owner,
+            initial_group,
+            users,
+            groups,
+            message: None,
+        };
+        dialog.owner.move_cursor(tui_textarea::CursorMove::End);
+        dialog.group.move_cursor(tui_textarea::CursorMove::End);
+        dialog.reset_octal();
+        Ok(dialog)
+    }
+
+    /// The edits the grid and the octal field change, and the mode bits of
+    /// the entries they apply to.
+    fn edited(&self) -> (ModeEdit, ModeBits) {
+        match (self.recursive, self.editing_files) {
+            (false, _) => (self.edit, self.entries),
+            (true, false) => (self.edit, self.dirs),
+            (true, true) => (self.file_edit, self.files),
+        }
+    }
+
+    fn set_edited(&mut self, edit: ModeEdit) {
+        if self.recursive && self.editing_files {
+            self.file_edit = edit;
+        } else {
+            self.edit = edit;
+        }
+    }
+
+    fn reset_octal(&mut self) {
+        let (edit, bits) = self.edited();
+        let text = match edit.shown(bits) {
+            (mode, 0) => format!("{mode:04o}"),
+            _ => String::new(),
+        };
+        self.octal = TextArea::from([text]);
+        self.octal.move_cursor(tui_textarea::CursorMove::End);
+        self.update_blocks();
+    }
+
+    /// Highlight the focused text field.
+    fn update_blocks(&mut self) {
+        let focus = self.focus;
+        let (edit, bits) = self.edited();
+        let octal = match edit.shown(bits) {
+            (_, 0) => "Octal",
+            _ => "Octal (modes differ)",
+        };
+        let block = |title: &'static str, field: Field| {
+            let style = if focus == field {
+                Style::new().yellow()
+            } else {
+                Style::new().dark_gray()
+            };
+            Block::bordered().title(title).border_style(style)
+        };
+        self.octal.set_block(block(octal, Field::Octal));
+        self.owner
+            .set_block(block("Owner (Tab completes)", Field::Owner));
+        self.group
+            .set_block(block("Group (Tab completes)", Field::Group));
+    }
+
+    fn move_focus(&mut self, forward: bool) {
+        // an invalid octal input falls back to the last valid mode
+        if self.focus == Field::Octal {
+            self.reset_octal();
+        }
+        self.focus = self.focus.step(forward);
+        self.message = None;
+        self.update_blocks();
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        match (self.focus, key.code) {
+            (_, KeyCode::Down) => self.move_focus(true),
+            (_, KeyCode::Up) => self.move_focus(false),
+            (Field::Owner | Field::Group, KeyCode::Tab) => self.complete(),
+            (_, KeyCode::Tab) => self.move_focus(true),
+            (_, KeyCode::BackTab) => self.move_focus(false),
+            (Field::Recursive, KeyCode::Char(' ') | KeyCode::Char('x')) => {
+                self.recursive = !self.recursive;
+                self.editing_files = false;
+                self.reset_octal();
+            }
+            (Field::Recursive, KeyCode::Left | KeyCode::Right | KeyCode::Char('h' | 'l'))
+                if self.recursive =>
+            {
+                self.editing_files = !self.editing_files;
+                self.reset_octal();
+            }
+            (Field::Grid(_), KeyCode::Left | KeyCode::Char('h')) => {
+                self.column = (self.column + 2) % 3;
+            }
+            (Field::Grid(_), KeyCode::Right | KeyCode::Char('l')) => {
+                self.column = (self.column + 1) % 3;
+            }
+            (Field::Grid(row), KeyCode::Char(' ') | KeyCode::Char('x')) => {
+                let bit = GRID[row].1[self.column];
+                let (mut edit, bits) = self.edited();
+                edit.toggle(bit, bits);
+                self.set_edited(edit);
+                self.reset_octal();
+            }
+            (Field::Octal, _) => {
+                self.octal.input(Event::Key(key));
+                let text = self.octal.lines().join("");
+                match u32::from_str_radix(&text, 8) {
+                    Ok(mode) if text.len() <= 4 && mode <= 0o7777 => {
+                        self.set_edited(ModeEdit::exactly(mode));
+                        self.message = None;
+                        self.update_blocks();
+                    }
+                    _ => self.message = Some((format!("{text:?} is not an octal mode"), true)),
+                }
+            }
+            (Field::Owner, _) => {
+                self.owner.input(Event::Key(key));
+                self.message = None;
+            }
+            (Field::Group, _) => {
+                self.group.input(Event::Key(key));
+                self.message = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Complete the owner or group name from the user database.
+    fn complete(&mut self) {
+        let (field, all_names) = match self.focus {
+            Field::Owner => (&mut self.owner, &self.users),
+            Field::Group => (&mut self.group, &self.groups),
+            _ => return,
+        };
+        let typed = field.lines().join("");
+        let names: Vec<&str> = all_names
+            .iter()
+            .map(String::as_str)
+            .filter(|name| name.starts_with(typed.as_str()))
+            .collect();
+        let completed = match names.as_slice() {
+            [] => {
+                self.message = Some(("no matches".to_string(), true));
+                return;
+            }
+            [only] => {
+                self.message = None;
+                only.to_string()
+            }
+            _ => {
+                self.message = Some((names.join("  "), false));
+                goto::common_prefix(&names).to_string()
+            }
+        };
+        *field = TextArea::from([completed]);
+        field.move_cursor(tui_textarea::CursorMove::End);
+        self.update_blocks();
+    }
+
+    /// What to apply, or why the input can't be applied.
+    fn changes(&self) -> std::result::Result<Changes, String> {
+        if let Some((err, true)) = &self.message
+            && self.focus == Field::Octal
+        {
+            return Err(err.clone());
+        }
+        // a name, or else a number
+        let lookup = |field: &TextArea, initial: &str, id_of: fn(&str) -> Option<u32>, kind| {
+            let name = field.lines().join("");
+            let name = name.trim();
+            if name == initial {
+                return Ok(None);
+            }
+            id_of(name)
+                .or_else(|| name.parse().ok())
+                .map(Some)
+                .ok_or_else(|| format!("unknown {kind} {name:?}"))
+        };
+        Ok(Changes {
+            edit: self.edit,
+            file_edit: self.file_edit,
+            recursive: self.recursive,
+            uid: lookup(&self.owner, &self.initial_owner, sys::user_id, "user")?,
+            gid: lookup(&self.group, &self.initial_group, sys::group_id, "group")?,
+        })
+    }
+}
+
+impl App {
+    pub fn open_permissions(&mut self) {
+        let paths: Vec<PathBuf> = self
+            .marked_or_selected_paths()
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+        if paths.is_empty() {
+            return;
+        }
+        match PermissionsDialog::new(paths) {
+            Ok(dialog) => self.permissions = Some(dialog),
+            Err(err) => self.notify_error(format!("Failed to read permissions: {err}")),
+        }
+    }
+
+    pub async fn handle_permissions_input(&mut self, key: KeyEvent) {
+        let Some(dialog) = &mut self.permissions else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.permissions = None;
+            }
+            KeyCode::Enter => match dialog.changes() {
+                Ok(changes) => {
+                    let paths = std::mem::take(&mut dialog.paths);
+                    self.permissions = None;
+                    self.apply_permissions(paths, changes).await;
+                }
+                Err(err) => dialog.message = Some((err, true)),
+            },
+            _ => dialog.handle_key(key),
+        }
+    }
+
+    async fn apply_permissions(&mut self, paths: Vec<PathBuf>, changes: Changes) {
+        let result = tokio::task::spawn_blocking(move || {
+            let mut errors = Vec::new();
+            let changed: usize = paths
+                .iter()
+                .map(|path| changes.apply(path, true, &mut errors))
+                .sum();
+            (changed, errors)
+        })
+        .await;
+        match result {
+            Ok((changed, errors)) => {
+                for err in errors.iter().take(MAX_REPORTED_ERRORS) {
+                    self.notify_error(format!("Failed to change permissions of {err}"));
+                }
+                if errors.len() > MAX_REPORTED_ERRORS {
+                    self.notify_error(format!(
+                        "… and {} more errors",
+                        errors.len() - MAX_REPORTED_ERRORS
+                    ));
+                }
+                if changed > 0 {
+                    self.notify_info(format!("Changed permissions of {changed} entries"));
+                }
+            }
+            Err(err) => self.notify_error(format!("Changing permissions failed: {err}")),
+        }
+        self.marked.clear();
+        self.rescan();
+    }
+
+    pub fn render_permissions(&self, frame: &mut Frame) {
+        use ratatui::layout::{Constraint, Layout};
+        use ratatui::style::Color;
+        use ratatui::widgets::{BorderType, Clear};
+
+        let Some(dialog) = &self.permissions else {
+            return;
+        };
+        let area = centered_rect(60, 70, frame.area());
+        frame.render_widget(Clear, area);
+        let title = match dialog.paths.as_slice() {
+            [path] => format!(
+                " 🔐 Permissions: {} ",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            paths => format!(" 🔐 Permissions: {} entries ", paths.len()),
+        };
+        let block = Block::bordered()
+            .title(title)
+            .title_bottom(" ↑↓:Field │ ←→:Column │ Space:Toggle │ Enter:Apply │ Esc:Cancel ")
+            .title_style(Style::new().bold().yellow())
+            .border_type(BorderType::Rounded)
+            .border_style(Style::new().yellow())
+            .style(Style::new().bg(Color::Rgb(30, 30, 40)));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let [grid_area, octal_area, owner_area, message_area] = Layout::vertical([
+            Constraint::Length(9),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(0),
+        ])
+        .areas(inner);
+
+        let checkbox = |on: bool| if on { "[x]" } else { "[ ]" };
+        // a bit set in only some of the entries
+        let mixed = "[-]";
+        let focused = |field: Field| dialog.focus == field;
+        let label_style = |field: Field| {
+            if focused(field) {
+                Style::new().yellow().bold()
+            } else {
+                Style::new()
+            }
+        };
+
+        // recursive switch and which mask the grid edits
+        let mut recursive = vec![
+            Span::styled(" Recursive  ", label_style(Field::Recursive)),
+            Span::styled(
+                checkbox(dialog.recursive),
+                if focused(Field::Recursive) {
+                    Style::new().reversed()
+                } else {
+                    Style::new()
+                },
+            ),
+        ];
+        if dialog.recursive {
+            let tab = |label: String, active: bool| {
+                if active {
+                    Span::from(format!(" {label} ")).black().on_yellow()
+                } else {
+                    Span::from(format!(" {label} ")).dark_gray()
+                }
+            };
+            recursive.push("   Editing: ".into());
+            recursive.push(tab(
+                format!("Directories {}", dialog.edit.label(dialog.dirs)),
+                !dialog.editing_files,
+            ));
+            recursive.push(" ".into());
+            recursive.push(tab(
+                format!("Files {}", dialog.file_edit.label(dialog.files)),
+                dialog.editing_files,
+            ));
+        }
+
+        let (edit, bits) = dialog.edited();
+        let (on, differ) = edit.shown(bits);
+        let mut lines = vec![Line::from(recursive), Line::default()];
+        for (row, (name, bits)) in GRID.iter().enumerate() {
+            let headers: [&str; 3] = if row == 3 {
+                ["setuid", "setgid", "sticky"]
+            } else {
+                ["read", "write", "exec"]
+            };
+            if row == 0 || row == 3 {
+                let header: String = headers.iter().map(|h| format!("{h:<8}")).collect();
+                lines.push(Line::from(format!("            {header}")).dark_gray());
+            }
+            let mut spans = vec![Span::styled(
+                format!(" {name:<10} "),
+                label_style(Field::Grid(row)),
+            )];
+            for (column, bit) in bits.iter().enumerate() {
+                let style = if focused(Field::Grid(row)) && dialog.column == column {
+                    Style::new().reversed()
+                } else {
+                    Style::new()
+                };
+                let shown = if differ & bit != 0 {
+                    mixed
+                } else {
+                    checkbox(on & bit != 0)
+                };
+                spans.push(Span::styled(shown, style));
+                spans.push("     ".into());
+            }
+            lines.push(Line::from(spans));
+        }
+        frame.render_widget(Paragraph::new(lines), grid_area);
+
+        frame.render_widget(&dialog.octal, octal_area);
+        let [user_area, group_area] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(owner_area);
+        frame.render_widget(&dialog.owner, user_area);
+        frame.render_widget(&dialog.group, group_area);
+
+        if let Some((message, is_error)) = &dialog.message {
+            let line = if *is_error {
+                Line::from(format!("✗ {message}")).red()
+            } else {
+                Line::from(message.as_str()).dark_gray()
+            };
+            frame.render_widget(
+                Paragraph::new(line).wrap(ratatui::widgets::Wrap { trim: true }),
+                message_area,
+            );
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    fn mode_of(path: &Path) -> u32 {
+        sys::mode(&fs::metadata(path).unwrap())
+    }
+
+    fn changes(edit: ModeEdit, file_edit: ModeEdit, recursive: bool) -> Changes {
+        Changes {
+            edit,
+            file_edit,
+            recursive,
+            uid: None,
+            gid: None,
+        }
+    }
+
+    #[test]
+    fn looks_up_users_and_groups() {
+        assert_eq!(sys::user_id("root"), Some(0));
+        assert_eq!(sys::group_id("root"), Some(0));
+        assert_eq!(sys::user_name(0).as_deref(), Some("root"));
+        assert_eq!(sys::group_name(0).as_deref(), Some("root"));
+        assert_eq!(sys::user_id("no-such-user-tfm"), None);
+        assert!(sys::user_names().iter().any(|name| name == "root"));
+        assert!(sys::group_names().iter().any(|name| name == "root"));
+    }
+
+    #[test]
+    fn mode_bits_of_differing_entries() {
+        let bits = ModeBits::of([0o644, 0o600]);
+        assert_eq!(
+            bits,
+            ModeBits {
+                all: 0o600,
+                any: 0o644
+            }
+        );
+        assert_eq!(ModeEdit::default().shown(bits), (0o600, 0o044));
+        assert_eq!(ModeEdit::default().label(bits), "mixed");
+        assert_eq!(ModeBits::of([]).any, 0o7777);
+    }
+
+    #[test]
+    fn toggled_bits_keep_the_others() {
+        let bits = ModeBits::of([0o644, 0o600]);
+        let mut edit = ModeEdit::default();
+        // group read is mixed, so the first toggle turns it on everywhere
+        edit.toggle(0o040, bits);
+        edit.toggle(0o100, bits);
+        assert_eq!(edit.apply(0o644), 0o744);
+        assert_eq!(edit.apply(0o600), 0o740);
+        edit.toggle(0o040, bits);
+        assert_eq!(edit.apply(0o644), 0o704);
+        assert_eq!(ModeEdit::exactly(0o755).apply(0o4600), 0o755);
+    }
+
+    #[test]
+    fn untouched_modes_are_left_alone() {
+        let dir = temp_dir("chmod-untouched");
+        let (private, script) = (dir.join("private"), dir.join("script"));
+        fs::write(&private, "").unwrap();
+        fs::write(&script, "").unwrap();
+        sys::set_mode(&private, 0o600).unwrap();
+        sys::set_mode(&script, 0o755).unwrap();
+
+        let unchanged = changes(ModeEdit::default(), ModeEdit::default(), false);
+        let mut errors = Vec::new();
+        assert_eq!(unchanged.apply(&private, true, &mut errors), 0);
+        assert_eq!(unchanged.apply(&script, true, &mut errors), 0);
+        assert!(errors.is_empty());
+        assert_eq!(mode_of(&private), 0o600);
+        assert_eq!(mode_of(&script), 0o755);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn recursive_modes_follow_the_entry_type() {
+        let dir = temp_dir("chmod-recursive");
+        let (file, sub) = (dir.join("file"), dir.join("sub"));
+        fs::write(&file, "").unwrap();
+        fs::create_dir(&sub).unwrap();
+        fs::write(sub.join("inner"), "").unwrap();
+
+        let recursive = changes(ModeEdit::exactly(0o750), ModeEdit::exactly(0o640), true);
+        let mut errors = Vec::new();
+        // the file comes first, as when it's the first marked entry
+        recursive.apply(&file, true, &mut errors);
+        recursive.apply(&sub, true, &mut errors);
+        assert!(errors.is_empty());
+        assert_eq!(mode_of(&file), 0o640);
+        assert_eq!(mode_of(&sub), 0o750);
+        assert_eq!(mode_of(&sub.join("inner")), 0o640);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,17 +1,115 @@
 use super::*;
 
+/// Below this width the miller layout drops the parent column.
+const MILLER_MIN_WIDTH: u16 = 90;
+/// Below this width only the current directory is shown.
+const PREVIEW_MIN_WIDTH: u16 = 40;
+
+/// Screen areas of the directory columns.
+struct Columns {
+    parent: Option<Rect>,
+    current: Rect,
+    preview: Option<Rect>,
+    /// The unfocused pane in dual-pane mode.
+    other: Option<Rect>,
+}
+
 impl App {
+    fn column_areas(&self, area: Rect) -> Columns {
+        use ratatui::layout::{Constraint, Layout};
+
+        if area.width < PREVIEW_MIN_WIDTH {
+            return Columns {
+                parent: None,
+                current: area,
+                preview: None,
+                other: None,
+            };
+        }
+        if self.other_pane.is_some() {
+            let [left, right] =
+                Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .areas(area);
+            let (current, other) = if self.focus_right {
+                (right, left)
+            } else {
+                (left, right)
+            };
+            return Columns {
+                parent: None,
+                current,
+                preview: None,
+                other: Some(other),
+            };
+        }
+        if self.layout == LayoutMode::Miller && area.width >= MILLER_MIN_WIDTH {
+            let ratios = self.config.column_ratios;
+            let total: u32 = ratios.iter().map(|r| *r as u32).sum();
+            let [parent, current, preview] =
+                Layout::horizontal(ratios.map(|r| Constraint::Ratio(r as u32, total))).areas(area);
+            return Columns {
+                parent: Some(parent),
+                current,
+                preview: Some(preview),
+                other: None,
+            };
+        }
+        let [current, preview] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(area);
+        Columns {
+            parent: None,
+            current,
+            preview: Some(preview),
+            other: None,
+        }
+    }
+
     pub fn render(&mut self, frame: &mut Frame) {
         use ratatui::style::Color;
         use ratatui::widgets::BorderType;
 
-        let items = self.dir.entries_with_symbols();
+        let mut main_area = Rect {
+            x: 0,
+            y: 0,
+            width: frame.area().width,
+            height: frame.area().height.saturating_sub(3),
+        };
+        if self.tabs.len() > 1 && main_area.height > 1 {
+            self.render_tab_bar(
+                frame,
+                Rect {
+                    height: 1,
+                    ..main_area
+                },
+            );
+            main_area.y += 1;
+            main_area.height -= 1;
+        }
+        let columns = self.column_areas(main_area);
+
+        let mut title = format!(" 📁 {} ", self.dir.path);
+        // a rescan keeps showing the old listing until it's done
+        if self.is_loading(ScanTarget::Current) && self.rescan.is_none() {
+            title.push_str(&format!("⏳ loading… ({}) ", self.dir.total_len()));
+        }
+        if self.dir.sort_key != SortKey::Name {
+            title.push_str(&format!("↕ {} ", self.dir.sort_key.label()));
+        }
+        if let Some(filter) = &self.dir.filter {
+            title.push_str(&format!(
+                "🔍 {filter} ({}/{}) ",
+                self.dir.len(),
+                self.dir.total_len()
+            ));
+        }
 
         // Main directory list with styled border
-        let list = List::new(items)
+        let list = EntryList::new(&self.dir)
+            .marked(&self.marked)
             .block(
                 Block::bordered()
-                    .title(format!(" 📁 {} ", self.dir.path))
+                    .title(title)
                     .title_style(Style::new().bold().cyan())
                     .border_type(BorderType::Rounded)
                     .border_style(Style::new().cyan()),
@@ -23,14 +121,14 @@ impl App {
                     .fg(Color::Rgb(255, 215, 0))
                     .bold(),
             )
-            .highlight_symbol("▶ ")
-            .repeat_highlight_symbol(true)
-            .direction(ListDirection::TopToBottom);
+            .highlight_symbol("▶ ");
 
         let helper_text = Text::from(
             Line::from(vec![
                 " q:Quit ".into(),
                 "│".dark_gray(),
+                " Ctrl-P:All actions ".into(),
+                "│".dark_gray(),
                 " ↑↓/jk:Nav ".into(),
                 "│".dark_gray(),
                 " ←→/hl:Dir ".into(),
@@ -49,55 +147,67 @@ impl App {
                 " x:Cut ".into(),
                 "│".dark_gray(),
                 " p:Paste ".into(),
+                "│".dark_gray(),
+                " Space:Mark ".into(),
+                "│".dark_gray(),
+                " !:Cmd ".into(),
+                "│".dark_gray(),
+                " S:Shell ".into(),
             ])
             .style(Style::new().fg(Color::Rgb(200, 200, 200))),
         );
 
         // Render main list
-        frame.render_stateful_widget(
-            list,
-            Rect {
-                x: 0,
-                y: 0,
-                width: frame.area().width / 2,
-                height: frame.area().height - 3,
-            },
-            &mut self.list_state,
-        );
-
-        // Preview panel
-        let items2 = if let Some(subdir) = &self.subdir {
-            subdir.entries_with_symbols()
-        } else {
-            vec!["   No preview available".to_string()]
-        };
+        frame.render_stateful_widget(list, columns.current, &mut self.list_state);
 
-        let preview_title = if let Some(subdir) = &self.subdir {
-            format!(" 👁  Preview: {} ", subdir.name)
-        } else {
-            " 👁  Preview ".to_string()
-        };
+        // Parent column (miller layout) with the current directory highlighted
+        if let Some(parent_area) = columns.parent {
+            let parent_block = Block::bordered()
+                .title(" ⬆ ")
+                .title_style(Style::new().bold().blue())
+                .border_type(BorderType::Rounded)
+                .border_style(Style::new().blue());
+            match &self.parent_dir {
+                Some(parent) => {
+                    let mut parent_state = ListState::default();
+                    parent_state.select(parent.position(&self.dir.name));
+                    let parent_list = EntryList::new(parent)
+                        .block(parent_block)
+                        .style(Style::new().fg(Color::Rgb(150, 150, 170)))
+                        .highlight_style(Style::new().bg(Color::Rgb(50, 50, 65)).white().bold());
+                    frame.render_stateful_widget(parent_list, parent_area, &mut parent_state);
+                }
+                None => frame.render_widget(parent_block, parent_area),
+            }
+        }
 
-        let list2 = List::new(items2)
-            .block(
-                Block::bordered()
-                    .title(preview_title)
-                    .title_style(Style::new().bold().magenta())
-                    .border_type(BorderType::Rounded)
-                    .border_style(Style::new().magenta()),
-            )
-            .style(Style::new().fg(Color::Rgb(180, 180, 200)))
-            .direction(ListDirection::TopToBottom);
+        // Unfocused pane in dual-pane mode
+        if let Some(other_area) = columns.other
+            && let Some(pane) = &mut self.other_pane
+        {
+            let mut title = format!(" 📁 {} ", pane.dir.path);
+            if self.other_scan.is_some() {
+                title.push_str("⏳ loading… ");
+            }
+            let other_list = EntryList::new(&pane.dir)
+                .marked(&pane.marked)
+                .block(
+                    Block::bordered()
+                        .title(title)
+                        .title_style(Style::new().fg(Color::Rgb(150, 150, 170)))
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::new().dark_gray()),
+                )
+                .style(Style::new().fg(Color::Rgb(150, 150, 170)))
+                .highlight_style(Style::new().bg(Color::Rgb(50, 50, 65)))
+                .highlight_symbol("  ");
+            frame.render_stateful_widget(other_list, other_area, &mut pane.list_state);
+        }
 
-        frame.render_widget(
-            list2,
-            Rect {
-                x: frame.area().width / 2,
-                y: 0,
-                width: frame.area().width / 2,
-                height: frame.area().height - 3,
-            },
-        );
+        // Preview panel (dropped on very narrow terminals)
+        if let Some(preview_area) = columns.preview {
+            self.render_preview(frame, preview_area);
+        }
 
         // Status bar at bottom
         frame.render_widget(
@@ -108,9 +218,9 @@ impl App {
             ),
             Rect {
                 x: 0,
-                y: frame.area().height - 3,
+                y: frame.area().height.saturating_sub(3),
                 width: frame.area().width,
-                height: 3,
+                height: 3.min(frame.area().height),
             },
         );
 
@@ -133,6 +243,7 @@ impl App {
             use ratatui::widgets::BorderType;
 
             let area = centered_rect(60, 25, frame.area());
+            frame.render_widget(ratatui::widgets::Clear, area);
             let block = Block::bordered()
                 .title(" ✏️  Rename File ")
                 .title_style(Style::new().bold().yellow())
@@ -142,7 +253,15 @@ impl App {
             let inner = block.inner(area);
             frame.render_widget(block, area);
 
-            frame.render_widget(&self.rename_input, inner);
+            let [input_area, error_area] = ratatui::layout::Layout::vertical([
+                ratatui::layout::Constraint::Length(3),
+                ratatui::layout::Constraint::Min(0),
+            ])
+            .areas(inner);
+            frame.render_widget(&self.rename_input, input_area);
+            if let Some(err) = &self.rename_error {
+                frame.render_widget(Paragraph::new(format!(" ✗ {err}")).red(), error_area);
+            }
         }
 
         if self.show_new_file {
@@ -151,7 +270,11 @@ impl App {
 
             let area = centered_rect(60, 25, frame.area());
             let block = Block::bordered()
-                .title(" ➕ New File ")
+                .title(if self.new_directory {
+                    " ➕ New Directory "
+                } else {
+                    " ➕ New File "
+                })
                 .title_style(Style::new().bold().green())
                 .border_type(BorderType::Rounded)
                 .border_style(Style::new().green())
@@ -161,5 +284,120 @@ impl App {
 
             frame.render_widget(&self.new_file_input, inner);
         }
+
+        if self.show_shell_prompt {
+            use ratatui::style::Color;
+            use ratatui::widgets::BorderType;
+
+            let area = centered_rect(70, 25, frame.area());
+            let block = Block::bordered()
+                .title(" ❯ Shell Command ")
+                .title_style(Style::new().bold().blue())
+                .border_type(BorderType::Rounded)
+                .border_style(Style::new().blue())
+                .style(Style::new().bg(Color::Rgb(30, 30, 40)));
+            let inner = block.inner(area);
+            frame.render_widget(block, area);
+
+            frame.render_widget(&self.shell_input, inner);
+        }
+
+        if self.show_filter {
+            use ratatui::style::Color;
+            use ratatui::widgets::Clear;
+
+            // small bar at the bottom so the filtered list stays visible
+            let area = Rect {
+                x: columns.current.x,
+                y: frame.area().height.saturating_sub(6),
+                width: columns.current.width,
+                height: 3,
+            };
+            frame.render_widget(Clear, area);
+            self.filter_input
+                .set_style(Style::new().bg(Color::Rgb(30, 30, 40)));
+            frame.render_widget(&self.filter_input, area);
+        }
+
+        if self.show_palette {
+            self.render_palette(frame);
+        }
+
+        if self.show_bookmarks {
+            self.render_bookmarks(frame);
+        }
+
+        if self.pattern_rename.is_some() {
+            self.render_pattern_rename(frame);
+        }
+
+        if self.rename_plan.is_some() {
+            self.render_rename_plan(frame);
+        }
+
+        if self.permissions.is_some() {
+            self.render_permissions(frame);
+        }
+
+        if self.show_goto {
+            use ratatui::widgets::Clear;
+
+            let area = centered_rect(70, 20, frame.area());
+            frame.render_widget(Clear, area);
+            frame.render_widget(&self.goto_input, area);
+        }
+
+        if self.show_jump_prompt {
+            self.render_jump_prompt(frame);
+        }
+
+        if self.show_jump_list {
+            self.render_jump_list(frame);
+        }
+
+        if self.show_messages {
+            self.render_messages(frame);
+        }
+
+        self.render_toasts(frame);
+
+        if self.show_output_log {
+            let area = centered_rect(80, 70, frame.area());
+            if let Ok(lines) = self.output_log.lock() {
+                frame.render_widget(OutputLogPanel { lines: &lines }, area);
+            }
+        }
+    }
+
+    fn render_preview(&self, frame: &mut Frame, preview_area: Rect) {
+        use ratatui::style::Color;
+        use ratatui::widgets::BorderType;
+
+        let preview_block = |title: String| {
+            Block::bordered()
+                .title(title)
+                .title_style(Style::new().bold().magenta())
+                .border_type(BorderType::Rounded)
+                .border_style(Style::new().magenta())
+        };
+        let preview_style = Style::new().fg(Color::Rgb(180, 180, 200));
+
+        if let Some(subdir) = &self.subdir {
+            let mut preview_title = format!(" 👁  Preview: {} ", subdir.name);
+            if self.is_loading(ScanTarget::Preview) {
+                preview_title.push_str("⏳ loading… ");
+            }
+            let list2 = EntryList::new(subdir)
+                .block(preview_block(preview_title))
+                .style(preview_style);
+            frame.render_stateful_widget(list2, preview_area, &mut ListState::default());
+        } else {
+            frame.render_widget(
+                Paragraph::new("   No preview available")
+                    .block(preview_block(" 👁  Preview ".to_string()))
+                    .style(preview_style),
+                preview_area,
+            );
+        }
     }
 }
@@ -0,0 +1,221 @@
+use super::*;
+use crate::file_ops::{ScannedEntry, is_readable};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Which listing a background scan fills.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanTarget {
+    Current,
+    Preview,
+    Parent,
+    /// The unfocused pane of the dual-pane mode.
+    OtherPane,
+}
+
+/// A rescan of the current directory. It fills a fresh listing that replaces
+/// the shown one when done, so the old listing stays usable meanwhile.
+#[derive(Debug)]
+pub struct Rescan {
+    dir: Directory,
+    /// Selected row when the rescan started, to tell whether the user moved.
+    selected: Option<usize>,
+}
+
+/// A running background scan. Dropping the handle cancels the scan.
+#[derive(Debug)]
+pub struct ScanHandle {
+    id: u64,
+    cancel: Arc<AtomicBool>,
+}
+
+impl Drop for ScanHandle {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl App {
+    /// Clear the target listing and refill it from a scan on the blocking pool.
+    /// Entries arrive in batches as [`AppMessage::ScanBatch`]; any scan already
+    /// running for the same target is cancelled.
+    pub fn start_scan(&mut self, target: ScanTarget) {
+        if target == ScanTarget::Current {
+            self.rescan = None;
+        }
+        let sort_key = match (target, &self.other_pane) {
+            (ScanTarget::OtherPane, Some(pane)) => pane.sort_key,
+            _ => self.sort_key,
+        };
+        let Some(dir) = self.scan_target_dir(target) else {
+            return;
+        };
+        dir.clear();
+        dir.sort_key = sort_key;
+        let path = dir.path.clone();
+        self.spawn_scan(target, path);
+    }
+
+    fn spawn_scan(&mut self, target: ScanTarget, path: String) {
+        self.next_scan_id += 1;
+        let id = self.next_scan_id;
+        let cancel = Arc::new(AtomicBool::new(false));
+        let handle = Some(ScanHandle {
+            id,
+            cancel: Arc::clone(&cancel),
+        });
+        *self.scan_slot(target) = handle;
+
+        let messages = self.messages.clone();
+        let include_hidden_files = self.show_hidden_files;
+        tokio::task::spawn_blocking(move || {
+            let result = file_ops::scan_batches(&path, include_hidden_files, |entries| {
+                !cancel.load(Ordering::Relaxed)
+                    && messages
+                        .send(AppMessage::ScanBatch {
+                            target,
+                            id,
+                            entries,
+                        })
+                        .is_ok()
+            });
+            let _ = messages.send(AppMessage::ScanDone {
+                target,
+                id,
+                error: result.err().map(|e| e.to_string()),
+            });
+        });
+    }
+
+    /// Rescan the current directory in the background after it was changed,
+    /// keeping the selected entry and the scroll position. Set
+    /// `pending_selection` afterwards to select something else. The old
+    /// listing stays on screen until the new one is complete.
+    pub fn rescan(&mut self) {
+        self.pending_selection = self
+            .list_state
+            .selected()
+            .and_then(|i| self.dir.entry_name(i));
+        self.pending_offset = Some(self.list_state.offset());
+        let dir = Directory::new(self.dir.name.clone(), self.dir.path.clone());
+        self.rescan = Some(Rescan {
+            dir,
+            selected: self.list_state.selected(),
+        });
+        self.spawn_scan(ScanTarget::Current, self.dir.path.clone());
+    }
+
+    fn scan_target_dir(&mut self, target: ScanTarget) -> Option<&mut Directory> {
+        match target {
+            ScanTarget::Current => match &mut self.rescan {
+                Some(rescan) => Some(&mut rescan.dir),
+                None => Some(&mut self.dir),
+            },
+            ScanTarget::Preview => self.subdir.as_mut(),
+            ScanTarget::Parent => self.parent_dir.as_mut(),
+            ScanTarget::OtherPane => self.other_pane.as_mut().map(|pane| &mut pane.dir),
+        }
+    }
+
+    fn scan_slot(&mut self, target: ScanTarget) -> &mut Option<ScanHandle> {
+        match target {
+            ScanTarget::Current => &mut self.current_scan,
+            ScanTarget::Preview => &mut self.preview_scan,
+            ScanTarget::Parent => &mut self.parent_scan,
+            ScanTarget::OtherPane => &mut self.other_scan,
+        }
+    }
+
+    pub fn is_loading(&self, target: ScanTarget) -> bool {
+        match target {
+            ScanTarget::Current => self.current_scan.is_some(),
+            ScanTarget::Preview => self.preview_scan.is_some(),
+            ScanTarget::Parent => self.parent_scan.is_some(),
+            ScanTarget::OtherPane => self.other_scan.is_some(),
+        }
+    }
+
+    fn is_current_scan(&mut self, target: ScanTarget, id: u64) -> bool {
+        self.scan_slot(target).as_ref().is_some_and(|h| h.id == id)
+    }
+
+    pub fn on_scan_batch(&mut self, target: ScanTarget, id: u64, entries: Vec<ScannedEntry>) {
+        // batches from a cancelled scan may still be in the queue
+        if !self.is_current_scan(target, id) {
+            return;
+        }
+        if let Some(dir) = self.scan_target_dir(target) {
+            dir.add_entries(entries);
+        }
+    }
+
+    pub async fn on_scan_done(&mut self, target: ScanTarget, id: u64, error: Option<String>) {
+        if !self.is_current_scan(target, id) {
+            return;
+        }
+        match target {
+            ScanTarget::Current => {
+                self.current_scan = None;
+                if let Some(rescan) = self.rescan.take() {
+                    // stay where the user moved to while it was loading
+                    if self.list_state.selected() != rescan.selected {
+                        self.pending_selection = self
+                            .list_state
+                            .selected()
+                            .and_then(|i| self.dir.entry_name(i));
+                        self.pending_offset = Some(self.list_state.offset());
+                    }
+                    let old = std::mem::replace(&mut self.dir, rescan.dir);
+                    self.dir.sort_key = old.sort_key;
+                    self.dir.filter = old.filter;
+                }
+                self.dir.rebuild_view();
+                // an unreadable directory shows an empty state instead of an error
+                self.dir.readable = error.is_none() || is_readable(&self.dir.path);
+                if let Some(err) = error
+                    && self.dir.readable
+                {
+                    self.notify_error(format!("Failed to read {}: {err}", self.dir.path));
+                }
+
+                let len = self.dir.len();
+                let selected = self.list_state.selected().unwrap_or(0);
+                self.list_state
+                    .select(Some(selected.min(len.saturating_sub(1))));
+                if let Some(offset) = self.pending_offset.take() {
+                    *self.list_state.offset_mut() = offset;
+                }
+                if let Some(name) = self.pending_selection.take() {
+                    self.select_entry(&name);
+                }
+                self.update_subdir_preview_async().await;
+            }
+            ScanTarget::OtherPane => {
+                self.other_scan = None;
+                if let Some(pane) = &mut self.other_pane {
+                    pane.dir.rebuild_view();
+                    pane.dir.readable = error.is_none() || is_readable(&pane.dir.path);
+                    let len = pane.dir.len();
+                    let selected = pane.list_state.selected().unwrap_or(0);
+                    pane.list_state
+                        .select(Some(selected.min(len.saturating_sub(1))));
+                }
+            }
+            ScanTarget::Preview | ScanTarget::Parent => {
+                *self.scan_slot(target) = None;
+                let Some(dir) = self.scan_target_dir(target) else {
+                    return;
+                };
+                dir.rebuild_view();
+                dir.readable = error.is_none() || is_readable(&dir.path);
+                // keep unreadable directories around to say so
+                if error.is_some() && dir.readable {
+                    match target {
+                        ScanTarget::Parent => self.parent_dir = None,
+                        _ => self.subdir = None,
+                    }
+                }
+            }
+        }
+    }
+}
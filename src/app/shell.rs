@@ -0,0 +1,298 @@
+use super::*;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+/// Environment variable holding how many file manager subshells deep we are.
+pub const SHELL_LEVEL_VAR: &str = "TFM_LEVEL";
+
+/// Maximum number of lines kept in the background output log.
+const OUTPUT_LOG_LIMIT: usize = 1000;
+
+pub type OutputLog = Arc<Mutex<Vec<String>>>;
+
+/// Wrap a string in single quotes so the shell treats it as one word.
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+fn push_log_line(log: &OutputLog, line: String) {
+    if let Ok(mut log) = log.lock() {
+        log.push(line);
+        if log.len() > OUTPUT_LOG_LIMIT {
+            let excess = log.len() - OUTPUT_LOG_LIMIT;
+            log.drain(..excess);
+        }
+    }
+}
+
+impl App {
+    /// Hand the terminal over to a child process: detach input, leave the
+    /// alternate screen and raw mode.
+    pub fn suspend_tui(&mut self) {
+        use crossterm::{
+            execute,
+            terminal::{LeaveAlternateScreen, disable_raw_mode},
+        };
+
+        // dropping the stream stops its reader thread from stealing the child's input
+        self.input_events = None;
+        if let Err(e) = disable_raw_mode() {
+            self.notify_error(format!("Failed to disable raw mode: {e}"));
+        }
+        let _ = execute!(std::io::stdout(), LeaveAlternateScreen);
+    }
+
+    /// Take the terminal back after [`App::suspend_tui`].
+    pub fn resume_tui(&mut self) {
+        use crossterm::{
+            execute,
+            terminal::{EnterAlternateScreen, enable_raw_mode},
+        };
+
+        let _ = execute!(std::io::stdout(), EnterAlternateScreen);
+        if let Err(e) = enable_raw_mode() {
+            self.notify_error(format!("Failed to enable raw mode: {e}"));
+        }
+        self.input_events = Some(EventStream::new());
+        self.needs_clear = true;
+    }
+
+    pub fn open_shell_prompt(&mut self) {
+        self.show_shell_prompt = true;
+        self.shell_input = TextArea::default();
+        self.shell_input.set_block(
+            Block::bordered()
+                .title("Command (%f file, %s marked, %d dir, trailing & = background)"),
+        );
+    }
+
+    pub fn toggle_mark(&mut self) {
+        if let Some(i) = self.list_state.selected()
+            && let Some(selected_entry) = self.dir.entry_name(i)
+        {
+            if !self.marked.remove(&selected_entry) {
+                self.marked.insert(selected_entry);
+            }
+            self.select_next();
+        }
+    }
+
+    /// Full paths of the marked entries, or of the selected entry when nothing is marked.
+    pub fn marked_or_selected_paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = if self.marked.is_empty() {
+            Vec::new()
+        } else {
+            self.dir
+                .iter()
+                .filter(|e| self.marked.contains(&e.label()))
+                .map(|e| format!("{}/{}", self.dir.path, e.name()))
+                .collect()
+        };
+
+        if paths.is_empty()
+            && let Some(i) = self.list_state.selected()
+            && let Some(selected_entry) = self.dir.get(i)
+        {
+            paths.push(format!("{}/{}", self.dir.path, selected_entry.name()));
+        }
+        paths
+    }
+
+    /// Replace `%f`, `%s`, `%d` and `%%` in a command line.
+    pub fn expand_placeholders(&self, command: &str) -> String {
+        let selected = self
+            .list_state
+            .selected()
+            .and_then(|i| self.dir.get(i))
+            .map(|e| format!("{}/{}", self.dir.path, e.name()))
+            .unwrap_or_default();
+
+        let mut expanded = String::new();
+        let mut chars = command.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                expanded.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('f') => expanded.push_str(&shell_quote(&selected)),
+                Some('s') => {
+                    let marked: Vec<String> = self
+                        .marked_or_selected_paths()
+                        .iter()
+                        .map(|p| shell_quote(p))
+                        .collect();
+                    expanded.push_str(&marked.join(" "));
+                }
+                Some('d') => expanded.push_str(&shell_quote(&self.dir.path)),
+                Some('%') => expanded.push('%'),
+                Some(other) => {
+                    expanded.push('%');
+                    expanded.push(other);
+                }
+                None => expanded.push('%'),
+            }
+        }
+        expanded
+    }
+
+    pub async fn handle_shell_input(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Enter => {
+                let command = self.shell_input.lines().join("").trim().to_string();
+                self.show_shell_prompt = false;
+                if let Some(background) = command.strip_suffix('&') {
+                    let expanded = self.expand_placeholders(background.trim());
+                    self.run_background_command(expanded);
+                } else if !command.is_empty() {
+                    let expanded = self.expand_placeholders(&command);
+                    self.run_foreground_command(&expanded);
+                    self.rescan();
+                }
+            }
+            KeyCode::Esc => {
+                self.show_shell_prompt = false;
+            }
+            _ => {
+                self.shell_input.input(Event::Key(key));
+            }
+        }
+        Ok(())
+    }
+
+    /// Run a command with the terminal handed over to it, then wait for Enter.
+    pub fn run_foreground_command(&mut self, command: &str) {
+        self.suspend_tui();
+
+        match Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .current_dir(&self.dir.path)
+            .status()
+        {
+            Ok(status) => println!("\n[{status}]"),
+            Err(err) => println!("\nFailed to run command: {err}"),
+        }
+        println!("Press Enter to return...");
+        let mut line = String::new();
+        let _ = std::io::stdin().read_line(&mut line);
+
+        self.resume_tui();
+    }
+
+    /// Spawn a command whose stdout and stderr are appended to the output log.
+    pub fn run_background_command(&mut self, command: String) {
+        if command.is_empty() {
+            return;
+        }
+        push_log_line(&self.output_log, format!("$ {command}"));
+
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(&command)
+            .current_dir(&self.dir.path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+
+        let mut child = match child {
+            Ok(child) => child,
+            Err(err) => {
+                push_log_line(&self.output_log, format!("Failed to run command: {err}"));
+                return;
+            }
+        };
+
+        let stderr_reader = child.stderr.take().map(|stderr| {
+            let log = Arc::clone(&self.output_log);
+            let messages = self.messages.clone();
+            std::thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(|l| l.ok()) {
+                    push_log_line(&log, line);
+                    let _ = messages.send(AppMessage::Redraw);
+                }
+            })
+        });
+
+        let stdout = child.stdout.take();
+        let log = Arc::clone(&self.output_log);
+        let messages = self.messages.clone();
+        std::thread::spawn(move || {
+            if let Some(stdout) = stdout {
+                for line in BufReader::new(stdout).lines().map_while(|l| l.ok()) {
+                    push_log_line(&log, line);
+                    let _ = messages.send(AppMessage::Redraw);
+                }
+            }
+            if let Some(handle) = stderr_reader {
+                let _ = handle.join();
+            }
+            let status = match child.wait() {
+                Ok(status) => status.to_string(),
+                Err(err) => err.to_string(),
+            };
+            push_log_line(&log, format!("[{command}: {status}]"));
+            let _ = messages.send(AppMessage::Redraw);
+        });
+    }
+
+    /// Launch `$SHELL` in the current directory and rescan once it exits.
+    pub async fn drop_to_subshell(&mut self) {
+        let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+        let level = std::env::var(SHELL_LEVEL_VAR)
+            .ok()
+            .and_then(|l| l.parse::<u32>().ok())
+            .unwrap_or(0)
+            + 1;
+
+        self.suspend_tui();
+        if let Err(err) = Command::new(&shell)
+            .current_dir(&self.dir.path)
+            .env(SHELL_LEVEL_VAR, level.to_string())
+            .status()
+        {
+            self.notify_error(format!("Failed to launch {shell}: {err}"));
+        }
+        self.resume_tui();
+
+        self.rescan();
+    }
+
+    pub fn toggle_output_log(&mut self) {
+        self.show_output_log = !self.show_output_log;
+    }
+}
+
+pub struct OutputLogPanel<'a> {
+    pub lines: &'a [String],
+}
+
+impl Widget for OutputLogPanel<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        use ratatui::style::Color;
+        use ratatui::widgets::{BorderType, Clear};
+
+        Clear.render(area, buf);
+        let block = Block::bordered()
+            .title(" 📜 Command Output (O to close) ")
+            .title_style(Style::new().bold().blue())
+            .border_type(BorderType::Rounded)
+            .border_style(Style::new().blue())
+            .style(Style::new().bg(Color::Rgb(20, 20, 30)));
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        // show the tail of the log that fits in the panel
+        let visible = inner.height as usize;
+        let start = self.lines.len().saturating_sub(visible);
+        let text = Text::from(
+            self.lines[start..]
+                .iter()
+                .map(|l| Line::from(l.as_str()))
+                .collect::<Vec<_>>(),
+        );
+        Paragraph::new(text).render(inner, buf);
+    }
+}
@@ -0,0 +1,53 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const APP_DIR: &str = "tui-file-manager";
+
+/// Our directory under the XDG base directory `var`, or under `~/<default>`
+/// when it isn't set.
+fn xdg_dir(var: &str, default: &str) -> Option<PathBuf> {
+    let base = env::var_os(var)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(default)))?;
+    Some(base.join(APP_DIR))
+}
+
+/// `$XDG_DATA_HOME/tui-file-manager` (or `~/.local/share/tui-file-manager`).
+pub fn data_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+/// `$XDG_STATE_HOME/tui-file-manager` (or `~/.local/state/tui-file-manager`).
+pub fn state_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_STATE_HOME", ".local/state")
+}
+
+/// Read a data file of `key<TAB>value` lines. A missing file is empty.
+pub fn load_pairs(file: &str) -> Vec<(String, String)> {
+    let Some(path) = data_dir().map(|dir| dir.join(file)) else {
+        return Vec::new();
+    };
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Write a data file of `key<TAB>value` lines, creating the data directory.
+pub fn save_pairs<'a>(
+    file: &str,
+    pairs: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> io::Result<()> {
+    let dir = data_dir().ok_or_else(|| io::Error::other("no home directory"))?;
+    fs::create_dir_all(&dir)?;
+    let contents: String = pairs
+        .into_iter()
+        .map(|(key, value)| format!("{key}\t{value}\n"))
+        .collect();
+    fs::write(dir.join(file), contents)
+}
@@ -0,0 +1,138 @@
+use super::*;
+use std::mem;
+
+/// State of a tab that isn't on screen. The active tab lives in the `App`
+/// fields themselves and is swapped in and out when switching.
+#[derive(Debug)]
+pub struct Tab {
+    pub dir: Directory,
+    pub list_state: ListState,
+    pub marked: HashSet<String>,
+    pub sort_key: SortKey,
+    pub history: Vec<String>,
+    pub forward_history: Vec<String>,
+}
+
+impl Tab {
+    pub fn new(dir: Directory) -> Self {
+        let mut list_state = ListState::default();
+        list_state.select(Some(0));
+        Self {
+            dir,
+            list_state,
+            marked: HashSet::new(),
+            sort_key: SortKey::default(),
+            history: Vec::new(),
+            forward_history: Vec::new(),
+        }
+    }
+}
+
+impl App {
+    /// Exchange the active state with `tab`.
+    pub fn swap_state(&mut self, tab: &mut Tab) {
+        mem::swap(&mut self.dir, &mut tab.dir);
+        mem::swap(&mut self.list_state, &mut tab.list_state);
+        mem::swap(&mut self.marked, &mut tab.marked);
+        mem::swap(&mut self.sort_key, &mut tab.sort_key);
+        mem::swap(&mut self.history, &mut tab.history);
+        mem::swap(&mut self.forward_history, &mut tab.forward_history);
+    }
+
+    /// Exchange the active state with the tab stored at `index`.
+    fn swap_tab(&mut self, index: usize) {
+        let mut tabs = mem::take(&mut self.tabs);
+        self.swap_state(&mut tabs[index]);
+        self.tabs = tabs;
+    }
+
+    /// Make `index` the active tab, then rescan it so it reflects any changes
+    /// made while it was in the background.
+    pub fn switch_tab(&mut self, index: usize) {
+        if index == self.active_tab || index >= self.tabs.len() {
+            return;
+        }
+        // cancel scans that belong to the tab being left
+        self.current_scan = None;
+        self.preview_scan = None;
+        self.parent_scan = None;
+
+        self.swap_tab(self.active_tab);
+        self.swap_tab(index);
+        self.active_tab = index;
+        self.reload_active();
+    }
+
+    /// Rescan the active state after it was swapped in, keeping its selection.
+    pub fn reload_active(&mut self) {
+        self.pending_selection = self
+            .list_state
+            .selected()
+            .and_then(|i| self.dir.entry_name(i));
+        self.subdir = None;
+        self.parent_dir = None;
+        self.start_scan(ScanTarget::Current);
+        self.update_parent_listing();
+        self.sync_watches();
+    }
+
+    /// Open a new tab on the current directory, right after the active one.
+    pub fn new_tab(&mut self) {
+        let mut dir = Directory::new(self.dir.name.clone(), self.dir.path.clone());
+        dir.filter = self.dir.filter.clone();
+        let mut tab = Tab::new(dir);
+        tab.list_state.select(self.list_state.selected());
+        tab.sort_key = self.sort_key;
+
+        self.tabs.insert(self.active_tab + 1, tab);
+        self.switch_tab(self.active_tab + 1);
+    }
+
+    /// Close the active tab. The last tab can't be closed.
+    pub fn close_tab(&mut self) {
+        if self.tabs.len() <= 1 {
+            return;
+        }
+        let closing = self.active_tab;
+        let next = if closing + 1 < self.tabs.len() {
+            closing + 1
+        } else {
+            closing - 1
+        };
+        self.switch_tab(next);
+        self.tabs.remove(closing);
+        if self.active_tab > closing {
+            self.active_tab -= 1;
+        }
+    }
+
+    pub fn next_tab(&mut self) {
+        self.switch_tab((self.active_tab + 1) % self.tabs.len());
+    }
+
+    pub fn previous_tab(&mut self) {
+        self.switch_tab((self.active_tab + self.tabs.len() - 1) % self.tabs.len());
+    }
+
+    /// Tab bar across the top of the screen, shown when more than one tab is open.
+    pub fn render_tab_bar(&self, frame: &mut Frame, area: Rect) {
+        use ratatui::style::Color;
+        use ratatui::widgets::Tabs;
+
+        let titles = self.tabs.iter().enumerate().map(|(i, tab)| {
+            // the stored slot of the active tab is stale
+            let name = if i == self.active_tab {
+                &self.dir.name
+            } else {
+                &tab.dir.name
+            };
+            format!(" {}:{} ", i + 1, if name.is_empty() { "/" } else { name })
+        });
+        let tabs = Tabs::new(titles)
+            .select(self.active_tab)
+            .style(Style::new().fg(Color::Rgb(150, 150, 170)))
+            .highlight_style(Style::new().bg(Color::Rgb(60, 60, 80)).yellow().bold())
+            .divider("│".dark_gray());
+        frame.render_widget(tabs, area);
+    }
+}
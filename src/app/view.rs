@@ -0,0 +1,57 @@
+use super::*;
+
+impl App {
+    /// Switch to the next sort key, keeping the selected entry.
+    pub fn cycle_sort(&mut self) {
+        let selected_name = self
+            .list_state
+            .selected()
+            .and_then(|i| self.dir.entry_name(i));
+
+        self.sort_key = self.sort_key.next();
+        self.dir.set_sort_key(self.sort_key);
+        if let Some(subdir) = &mut self.subdir {
+            subdir.set_sort_key(self.sort_key);
+        }
+        if let Some(parent) = &mut self.parent_dir {
+            parent.set_sort_key(self.sort_key);
+        }
+        if let Some(name) = selected_name {
+            self.select_entry(&name);
+        }
+    }
+
+    pub fn open_filter_prompt(&mut self) {
+        self.show_filter = true;
+        let current = self.dir.filter.clone().unwrap_or_default();
+        self.filter_input = TextArea::from([current]);
+        self.filter_input.move_cursor(tui_textarea::CursorMove::End);
+        self.filter_input
+            .set_block(Block::bordered().title("Filter (Enter to keep, Esc to clear)"));
+    }
+
+    /// Filter the listing as the user types.
+    pub async fn handle_filter_input(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Enter => {
+                self.show_filter = false;
+            }
+            KeyCode::Esc => {
+                self.show_filter = false;
+                self.apply_filter(None).await;
+            }
+            _ => {
+                self.filter_input.input(Event::Key(key));
+                let filter = self.filter_input.lines().join("");
+                self.apply_filter(Some(filter)).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn apply_filter(&mut self, filter: Option<String>) {
+        self.dir.set_filter(filter);
+        self.list_state.select(Some(0));
+        self.update_subdir_preview_async().await;
+    }
+}
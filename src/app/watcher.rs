@@ -0,0 +1,124 @@
+use super::*;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// How long the filesystem has to be quiet before we rescan.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Watches the current directory and the previewed subdirectory for changes.
+#[derive(Debug)]
+pub struct FsWatcher {
+    watcher: RecommendedWatcher,
+    watched: Vec<PathBuf>,
+    dir_changed: bool,
+    subdir_changed: bool,
+    last_event: Option<Instant>,
+}
+
+impl FsWatcher {
+    /// Create a watcher that posts its events to the main loop.
+    pub fn new(messages: UnboundedSender<AppMessage>) -> notify::Result<Self> {
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                let _ = messages.send(AppMessage::FsEvent(event));
+            }
+        })?;
+        Ok(Self {
+            watcher,
+            watched: Vec::new(),
+            dir_changed: false,
+            subdir_changed: false,
+            last_event: None,
+        })
+    }
+
+    /// Replace the watched set with `paths` (non-recursive).
+    pub fn watch_only(&mut self, paths: &[&str]) {
+        let wanted: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+        for old in &self.watched {
+            if !wanted.contains(old) {
+                let _ = self.watcher.unwatch(old);
+            }
+        }
+        for new in &wanted {
+            if !self.watched.contains(new) {
+                let _ = self.watcher.watch(new, RecursiveMode::NonRecursive);
+            }
+        }
+        self.watched = wanted;
+    }
+
+    /// Note which of the watched directories an event touches.
+    pub fn record(&mut self, event: notify::Event, dir: &str, subdir: Option<&str>) {
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        for path in &event.paths {
+            let parent = path.parent();
+            if parent == Some(Path::new(dir)) || path == Path::new(dir) {
+                self.dir_changed = true;
+            }
+            if let Some(subdir) = subdir
+                && (parent == Some(Path::new(subdir)) || path == Path::new(subdir))
+            {
+                self.subdir_changed = true;
+            }
+        }
+        self.last_event = Some(Instant::now());
+    }
+
+    /// Returns `(dir_changed, subdir_changed)` once the debounce window has
+    /// passed without new events. A change of a directory that is being
+    /// scanned (`busy`) stays pending until the scan is done, as the scan may
+    /// already have read past the changed entry.
+    pub fn take_changes(&mut self, dir_busy: bool, subdir_busy: bool) -> (bool, bool) {
+        match self.last_event {
+            Some(last) if last.elapsed() >= DEBOUNCE => {
+                let changes = (
+                    self.dir_changed && !dir_busy,
+                    self.subdir_changed && !subdir_busy,
+                );
+                self.dir_changed &= dir_busy;
+                self.subdir_changed &= subdir_busy;
+                if !self.dir_changed && !self.subdir_changed {
+                    self.last_event = None;
+                }
+                changes
+            }
+            _ => (false, false),
+        }
+    }
+}
+
+impl App {
+    /// Point the watcher at the current directory and the previewed subdirectory.
+    pub fn sync_watches(&mut self) {
+        if let Some(watcher) = &mut self.watcher {
+            let mut paths = vec![self.dir.path.as_str()];
+            if let Some(subdir) = &self.subdir {
+                paths.push(subdir.path.as_str());
+            }
+            watcher.watch_only(&paths);
+        }
+    }
+
+    /// Rescan whatever the watcher reported as changed, keeping the selection by name.
+    /// Returns whether anything was refreshed.
+    pub fn refresh_on_fs_changes(&mut self) -> bool {
+        let dir_busy = self.is_loading(ScanTarget::Current);
+        let subdir_busy = self.is_loading(ScanTarget::Preview);
+        let Some(watcher) = &mut self.watcher else {
+            return false;
+        };
+        let (dir_changed, subdir_changed) = watcher.take_changes(dir_busy, subdir_busy);
+
+        if dir_changed {
+            self.rescan();
+        } else if subdir_changed && self.subdir.is_some() {
+            self.start_scan(ScanTarget::Preview);
+        }
+        dir_changed || subdir_changed
+    }
+}
@@ -6,6 +6,78 @@ pub struct Directory {
     pub path: String,
     pub files: Vec<FileEntry>,
     pub subdirectories: Vec<Directory>,
+    pub sort_key: SortKey,
+    pub filter: Option<String>,
+    /// Whether we may list the directory; unreadable ones get a lock.
+    pub readable: bool,
+    /// Sorted, filtered order of the entries, rebuilt only when the contents,
+    /// sort key or filter change.
+    view: Vec<ViewIndex>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    #[default]
+    Name,
+    Size,
+    Extension,
+}
+
+impl SortKey {
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Name => SortKey::Size,
+            SortKey::Size => SortKey::Extension,
+            SortKey::Extension => SortKey::Name,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Name => "name",
+            SortKey::Size => "size",
+            SortKey::Extension => "extension",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ViewIndex {
+    Dir(usize),
+    File(usize),
+}
+
+/// A borrowed entry of a [`Directory`] listing.
+#[derive(Debug, Clone, Copy)]
+pub enum Entry<'a> {
+    Dir(&'a Directory),
+    File(&'a FileEntry),
+}
+
+impl Entry<'_> {
+    pub fn name(&self) -> &str {
+        match self {
+            Entry::Dir(dir) => &dir.name,
+            Entry::File(file) => &file.name,
+        }
+    }
+
+    /// The name, with a trailing `/` for directories.
+    pub fn label(&self) -> String {
+        match self {
+            Entry::Dir(dir) => format!("{}/", dir.name),
+            Entry::File(file) => file.name.clone(),
+        }
+    }
+
+    /// The name with its file type symbol and size, as shown in listings.
+    pub fn display(&self) -> String {
+        match self {
+            Entry::Dir(dir) if !dir.readable => format!("🔒 {}/", dir.name),
+            Entry::Dir(dir) => format!("📂 {}/", dir.name),
+            Entry::File(file) => file.assign_symbol(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -41,6 +113,18 @@ impl Filesize {
 }
 
 impl FileEntry {
+    pub fn size_in_bytes(&self) -> u64 {
+        self.size.as_ref().map_or(0, |s| s.size_in_bytes)
+    }
+
+    /// Extension after the last `.`, or `""` if there is none.
+    pub fn extension(&self) -> &str {
+        match self.name.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => ext,
+            _ => "",
+        }
+    }
+
     pub fn enumerate_filetype(&self) -> FileType {
         if self.filetype != FileType::Unknown {
             return self.filetype.clone();
@@ -105,6 +189,7 @@ impl FromIterator<FileEntry> for Directory {
         for file in iter {
             dir.add_file(file);
         }
+        dir.rebuild_view();
         dir
     }
 }
@@ -116,6 +201,10 @@ impl Directory {
             path,
             files: Vec::new(),
             subdirectories: Vec::new(),
+            sort_key: SortKey::default(),
+            filter: None,
+            readable: true,
+            view: Vec::new(),
         }
     }
     fn add_file(&mut self, file: FileEntry) {
@@ -126,110 +215,223 @@ impl Directory {
         self.subdirectories.push(subdirectory);
     }
 
-    pub async fn scan_and_add(&mut self, include_hidden_files: bool) -> Result<()> {
+    pub fn clear(&mut self) {
         self.files.clear();
         self.subdirectories.clear();
+        self.view.clear();
+    }
 
-        use std::fs;
-
-        let entries = fs::read_dir(&self.path)?;
+    /// Append a batch of scanned entries. They are shown unsorted until
+    /// [`Directory::rebuild_view`] is called once scanning is done.
+    pub fn add_entries(&mut self, entries: Vec<ScannedEntry>) {
         for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file() {
-                if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                    if !include_hidden_files && file_name.starts_with('.') {
-                        continue;
+            match entry {
+                ScannedEntry::File(file) => {
+                    if self.matches_filter(&file.name) {
+                        self.view.push(ViewIndex::File(self.files.len()));
                     }
-                    let filesize = fs::File::open(&path)
-                        .ok()
-                        .and_then(|f| f.metadata().ok())
-                        .map(|meta| Filesize {
-                            size_in_bytes: meta.len(),
-                        });
-                    let mut file = FileEntry {
-                        name: file_name.to_string(),
-                        filetype: FileType::Unknown,
-                        size: filesize,
-                    };
-                    let filetype = file.enumerate_filetype();
-                    file.filetype = filetype;
-
                     self.add_file(file);
                 }
-            } else if path.is_dir()
-                && let Some(dir_name) = path.file_name().and_then(|n| n.to_str())
-            {
-                let subdirectory = Directory {
-                    name: dir_name.to_string(),
-                    path: path.to_str().unwrap().to_string(),
-                    files: Vec::new(),
-                    subdirectories: Vec::new(),
-                };
-                self.add_subdirectory(subdirectory);
+                ScannedEntry::Dir(dir) => {
+                    if self.matches_filter(&dir.name) {
+                        self.view.push(ViewIndex::Dir(self.subdirectories.len()));
+                    }
+                    self.add_subdirectory(dir);
+                }
             }
         }
+    }
 
-        self.subdirectories = self.sort_subdirectories().subdirectories;
-        self.files = self.sort_files().files;
-        Ok(())
+    fn matches_filter(&self, name: &str) -> bool {
+        match &self.filter {
+            Some(filter) => name.to_lowercase().contains(&filter.to_lowercase()),
+            None => true,
+        }
     }
 
-    pub fn entries(&self) -> Vec<String> {
-        let mut entries = Vec::new();
+    /// Recompute the sorted, filtered order: directories first, then files
+    /// ordered by the sort key.
+    pub fn rebuild_view(&mut self) {
+        let mut dirs: Vec<usize> = (0..self.subdirectories.len())
+            .filter(|&i| self.matches_filter(&self.subdirectories[i].name))
+            .collect();
+        dirs.sort_by(|&a, &b| {
+            self.subdirectories[a]
+                .name
+                .cmp(&self.subdirectories[b].name)
+        });
 
-        let sorted_dir = self.sort_subdirectories();
-        for subdir in &sorted_dir.subdirectories {
-            entries.push(format!("{}/", subdir.name));
+        let mut files: Vec<usize> = (0..self.files.len())
+            .filter(|&i| self.matches_filter(&self.files[i].name))
+            .collect();
+        let by_name = |a: &usize, b: &usize| self.files[*a].name.cmp(&self.files[*b].name);
+        match self.sort_key {
+            SortKey::Name => files.sort_by(by_name),
+            // largest first
+            SortKey::Size => files.sort_by(|a, b| {
+                self.files[*b]
+                    .size_in_bytes()
+                    .cmp(&self.files[*a].size_in_bytes())
+                    .then_with(|| by_name(a, b))
+            }),
+            SortKey::Extension => files.sort_by(|a, b| {
+                self.files[*a]
+                    .extension()
+                    .cmp(self.files[*b].extension())
+                    .then_with(|| by_name(a, b))
+            }),
         }
 
-        let sorted_files = self.sort_files();
-        for file in &sorted_files.files {
-            entries.push(file.name.clone());
-        }
+        self.view = dirs
+            .into_iter()
+            .map(ViewIndex::Dir)
+            .chain(files.into_iter().map(ViewIndex::File))
+            .collect();
+    }
+
+    pub fn set_sort_key(&mut self, sort_key: SortKey) {
+        self.sort_key = sort_key;
+        self.rebuild_view();
+    }
 
-        entries
+    pub fn set_filter(&mut self, filter: Option<String>) {
+        self.filter = filter.filter(|f| !f.is_empty());
+        self.rebuild_view();
     }
 
-    pub fn entries_with_symbols(&self) -> Vec<String> {
-        let mut entries = Vec::new();
+    /// Number of entries in the (filtered) listing.
+    pub fn len(&self) -> usize {
+        self.view.len()
+    }
 
-        let sorted_dir = self.sort_subdirectories();
-        for subdir in &sorted_dir.subdirectories {
-            entries.push(format!("📂 {}/", subdir.name));
-        }
+    pub fn is_empty(&self) -> bool {
+        self.view.is_empty()
+    }
+
+    /// Total number of entries, ignoring the filter.
+    pub fn total_len(&self) -> usize {
+        self.files.len() + self.subdirectories.len()
+    }
 
-        let sorted_files = self.sort_files();
-        for file in &sorted_files.files {
-            entries.push(file.assign_symbol())
+    pub fn get(&self, i: usize) -> Option<Entry<'_>> {
+        self.view.get(i).map(|index| self.resolve(*index))
+    }
+
+    fn resolve(&self, index: ViewIndex) -> Entry<'_> {
+        match index {
+            ViewIndex::Dir(i) => Entry::Dir(&self.subdirectories[i]),
+            ViewIndex::File(i) => Entry::File(&self.files[i]),
         }
+    }
 
-        entries
+    /// Entries in display order.
+    pub fn iter(&self) -> impl Iterator<Item = Entry<'_>> {
+        self.view.iter().map(|index| self.resolve(*index))
     }
 
-    fn sort_subdirectories(&self) -> Directory {
-        let mut sorted_dir = self.clone();
-        sorted_dir
-            .subdirectories
-            .sort_by(|a, b| a.name.cmp(&b.name));
-        sorted_dir.files.sort_by(|a, b| a.name.cmp(&b.name));
-        sorted_dir
+    /// Label (name, with `/` for directories) of the entry at `i`.
+    pub fn entry_name(&self, i: usize) -> Option<String> {
+        self.get(i).map(|entry| entry.label())
     }
 
-    fn sort_files(&self) -> Directory {
-        let mut sorted_dir = self.clone();
-        sorted_dir.files.sort_by(|a, b| a.name.cmp(&b.name));
-        sorted_dir
-            .subdirectories
-            .sort_by(|a, b| a.name.cmp(&b.name));
-        sorted_dir
+    /// Position of the entry called `name` (a trailing `/` is ignored).
+    pub fn position(&self, name: &str) -> Option<usize> {
+        let name = name.trim_end_matches('/');
+        self.iter().position(|entry| entry.name() == name)
     }
 }
 
+/// A single entry produced while scanning a directory.
+#[derive(Debug)]
+pub enum ScannedEntry {
+    File(FileEntry),
+    Dir(Directory),
+}
+
+/// Number of entries handed to the callback of [`scan_batches`] at a time.
+pub const SCAN_BATCH_SIZE: usize = 512;
+
+/// Read `path` (blocking), handing entries to `on_batch` in groups of
+/// [`SCAN_BATCH_SIZE`]. Scanning stops early when `on_batch` returns `false`.
+pub fn scan_batches(
+    path: &str,
+    include_hidden_files: bool,
+    mut on_batch: impl FnMut(Vec<ScannedEntry>) -> bool,
+) -> Result<()> {
+    use std::fs;
+
+    let mut batch = Vec::with_capacity(SCAN_BATCH_SIZE);
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        // a single stat (following symlinks) gives both the kind and the size
+        let Ok(meta) = fs::metadata(&path) else {
+            continue;
+        };
+
+        if meta.is_file() {
+            if !include_hidden_files && name.starts_with('.') {
+                continue;
+            }
+            let mut file = FileEntry {
+                name: name.to_string(),
+                filetype: FileType::Unknown,
+                size: Some(Filesize {
+                    size_in_bytes: meta.len(),
+                }),
+            };
+            file.filetype = file.enumerate_filetype();
+            batch.push(ScannedEntry::File(file));
+        } else if meta.is_dir() {
+            let mut dir = Directory::new(name.to_string(), path.to_string_lossy().to_string());
+            dir.readable = is_readable(&path);
+            batch.push(ScannedEntry::Dir(dir));
+        }
+
+        if batch.len() >= SCAN_BATCH_SIZE
+            && !on_batch(std::mem::replace(
+                &mut batch,
+                Vec::with_capacity(SCAN_BATCH_SIZE),
+            ))
+        {
+            return Ok(());
+        }
+    }
+    if !batch.is_empty() {
+        on_batch(batch);
+    }
+    Ok(())
+}
+
+/// Whether the directory at `path` can be listed and entered.
+#[cfg(unix)]
+pub fn is_readable(path: impl AsRef<std::path::Path>) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(path) = std::ffi::CString::new(path.as_ref().as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: `path` is a valid NUL-terminated string
+    unsafe { libc::access(path.as_ptr(), libc::R_OK | libc::X_OK) == 0 }
+}
+
+#[cfg(not(unix))]
+pub fn is_readable(path: impl AsRef<std::path::Path>) -> bool {
+    std::fs::read_dir(path).is_ok()
+}
+
 pub async fn get_current_directory() -> Result<Directory> {
     use std::env;
 
     let current_path = env::current_dir()?;
+    get_directory(&current_path).await
+}
+
+pub async fn get_directory(path: &std::path::Path) -> Result<Directory> {
+    let current_path = path.canonicalize()?;
     let dir_name = current_path
         .file_name()
         .and_then(|n| n.to_str())
@@ -241,3 +443,144 @@ pub async fn get_current_directory() -> Result<Directory> {
 
     Ok(directory)
 }
+
+/// Copy a file or a whole directory tree from `src` to `dst` (blocking).
+/// Symlinks are copied as links instead of being followed.
+pub fn copy_recursive(src: &std::path::Path, dst: &std::path::Path) -> Result<()> {
+    use std::fs;
+
+    // copying a directory into itself would never end
+    if fs::symlink_metadata(src)?.is_dir() {
+        let source = src.canonicalize()?;
+        let target_dir = dst
+            .parent()
+            .unwrap_or(std::path::Path::new("."))
+            .canonicalize()?;
+        if target_dir.starts_with(&source) {
+            anyhow::bail!("can't copy {} into itself", src.display());
+        }
+    }
+    copy_tree(src, dst)
+}
+
+fn copy_tree(src: &std::path::Path, dst: &std::path::Path) -> Result<()> {
+    use std::fs;
+
+    let meta = fs::symlink_metadata(src)?;
+    if meta.file_type().is_symlink() {
+        copy_symlink(src, dst)?;
+    } else if meta.is_dir() {
+        fs::create_dir(dst)?;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            copy_tree(&entry.path(), &dst.join(entry.file_name()))?;
+        }
+    } else {
+        fs::copy(src, dst)?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(src: &std::path::Path, dst: &std::path::Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(std::fs::read_link(src)?, dst)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(src: &std::path::Path, dst: &std::path::Path) -> std::io::Result<()> {
+    std::fs::copy(src, dst).map(|_| ())
+}
+
+/// Move `src` to `dst` (blocking), copying and deleting when it has to
+/// cross filesystems.
+pub fn move_path(src: &std::path::Path, dst: &std::path::Path) -> Result<()> {
+    use std::fs;
+
+    match fs::rename(src, dst) {
+        Ok(()) => return Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::CrossesDevices => {}
+        Err(err) => return Err(err.into()),
+    }
+    copy_recursive(src, dst)?;
+    if fs::symlink_metadata(src)?.is_dir() {
+        fs::remove_dir_all(src)?;
+    } else {
+        fs::remove_file(src)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+    use std::fs;
+
+    #[test]
+    fn copy_into_itself_is_refused() {
+        let dir = temp_dir("copy-into-itself");
+        let src = dir.join("src");
+        fs::create_dir_all(src.join("sub")).unwrap();
+        fs::write(src.join("file"), "x").unwrap();
+
+        let into_itself = copy_recursive(&src, &src.join("copy"));
+        let into_child = copy_recursive(&src, &src.join("sub").join("copy"));
+        let created = src.join("copy").exists() || src.join("sub").join("copy").exists();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(into_itself.unwrap_err().to_string().contains("into itself"));
+        assert!(into_child.is_err());
+        assert!(!created);
+    }
+
+    #[test]
+    fn copy_next_to_itself_is_allowed() {
+        let dir = temp_dir("copy-sibling");
+        let src = dir.join("src");
+        fs::create_dir(&src).unwrap();
+        fs::write(src.join("file"), "x").unwrap();
+
+        // a sibling that shares the source's name as a prefix isn't inside it
+        let result = copy_recursive(&src, &dir.join("src-copy"));
+        let copied = fs::read_to_string(dir.join("src-copy").join("file"));
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(result.is_ok(), "{result:?}");
+        assert_eq!(copied.unwrap(), "x");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_copied_as_links() {
+        let dir = temp_dir("copy-symlink");
+        let src = dir.join("src");
+        fs::create_dir(&src).unwrap();
+        fs::write(dir.join("outside"), "x").unwrap();
+        std::os::unix::fs::symlink("../outside", src.join("link")).unwrap();
+
+        let result = copy_recursive(&src, &dir.join("dst"));
+        let link = dir.join("dst").join("link");
+        let is_link = fs::symlink_metadata(&link).map(|m| m.file_type().is_symlink());
+        let target = fs::read_link(&link);
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(result.is_ok(), "{result:?}");
+        assert!(is_link.unwrap());
+        assert_eq!(target.unwrap(), std::path::Path::new("../outside"));
+    }
+
+    #[test]
+    fn move_returns_rename_errors_other_than_crossing_devices() {
+        let dir = temp_dir("move-error");
+        let (src, dst) = (dir.join("src"), dir.join("dst"));
+        fs::create_dir(&src).unwrap();
+        fs::write(src.join("file"), "x").unwrap();
+        fs::create_dir(&dst).unwrap();
+        fs::write(dst.join("other"), "y").unwrap();
+
+        // renaming onto a non-empty directory fails without copying anything
+        let err = move_path(&src, &dst).unwrap_err();
+        let kind = err.downcast_ref::<std::io::Error>().map(|e| e.kind());
+        let untouched = src.join("file").exists() && !dst.join("file").exists();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(kind, Some(std::io::ErrorKind::DirectoryNotEmpty));
+        assert!(untouched);
+    }
+}
@@ -1,24 +1,102 @@
 use color_eyre::Result;
 mod app;
 mod file_ops;
+#[cfg(test)]
+mod test_util;
 use app::App;
+use app::config::{self, Command};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
-    let terminal = ratatui::init();
+    let config = config::load_config();
+
+    match &config.command {
+        Some(Command::ShellInit { shell }) => {
+            print!("{}", shell.wrapper());
+            return Ok(());
+        }
+        Some(Command::Import { source, file }) => {
+            let imported = app::frecency::import(*source, file.as_deref())?;
+            println!("Imported {imported} directories");
+            return Ok(());
+        }
+        None => {}
+    }
 
-    let mut app = App::new().await;
-    app.dir.scan_and_add(app.show_hidden_files).await.unwrap();
+    // In pick mode stdout carries the result, so draw on the terminal instead
+    let saved_stdout = if config.pick {
+        redirect_stdout_to_tty()
+    } else {
+        None
+    };
 
-    let subdir = app.dir.subdirectories.first().cloned();
+    let terminal = ratatui::init();
+    app::crash::install_hook();
 
-    app.subdir = subdir;
-    // fs::write("./log", format!("{:?}", subdir)).unwrap();
+    let mut app = App::new(config).await;
 
     let result = app.run(terminal).await;
 
     ratatui::restore();
 
+    if let Some(saved) = saved_stdout {
+        restore_stdout(saved);
+    }
+
+    app.write_choice_files()?;
+    for path in &app.picked {
+        println!("{path}");
+    }
+
     result
 }
+
+/// Point stdout at `/dev/tty` when it is not a terminal, returning the original.
+#[cfg(unix)]
+fn redirect_stdout_to_tty() -> Option<std::os::fd::OwnedFd> {
+    use std::io::IsTerminal;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+    if std::io::stdout().is_terminal() {
+        return None;
+    }
+    let tty = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+
+    // SAFETY: plain descriptor duplication; the saved fd is owned by the returned OwnedFd
+    unsafe {
+        let saved = libc::dup(libc::STDOUT_FILENO);
+        if saved < 0 {
+            return None;
+        }
+        if libc::dup2(tty.as_raw_fd(), libc::STDOUT_FILENO) < 0 {
+            libc::close(saved);
+            return None;
+        }
+        Some(OwnedFd::from_raw_fd(saved))
+    }
+}
+
+#[cfg(unix)]
+fn restore_stdout(saved: std::os::fd::OwnedFd) {
+    use std::io::Write;
+    use std::os::fd::AsRawFd;
+
+    let _ = std::io::stdout().flush();
+    // SAFETY: `saved` is a valid descriptor for the original stdout
+    unsafe {
+        libc::dup2(saved.as_raw_fd(), libc::STDOUT_FILENO);
+    }
+}
+
+#[cfg(not(unix))]
+fn redirect_stdout_to_tty() -> Option<()> {
+    None
+}
+
+#[cfg(not(unix))]
+fn restore_stdout(_saved: ()) {}
@@ -0,0 +1,12 @@
+//! Helpers shared by the unit tests.
+
+use std::fs;
+use std::path::PathBuf;
+
+/// A fresh, empty directory in the system temp dir, named after the test.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("tfm-test-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}